@@ -1,229 +1,865 @@
+use crate::binder::callback_manager::{CallbackManager, HoverHandler, TemplateErrorHandler};
+use crate::binder::camera::{Camera, PinchState};
 use crate::binder::element_manager::ElementManager;
-use crate::binder::mouse_state::MouseState;
-use crate::figure::Figure;
+use crate::binder::js_table_content::{table_data_from_js, JsTableContent, TableData};
+use crate::binder::pointer_state::{PointerState, PointerStates, PointerType};
+use crate::expression::Template;
+use crate::figure::hover::HoverTarget;
+use crate::figure::{DeltaMode, Figure};
+use crate::figure_builder::FigureBuilder;
 use crate::math::Point;
+use crate::render_backend::{RenderBackend, WebSysBackend};
+use crate::state_store::{StateStore, StateValue};
+use crate::table_style::{CompiledTableStyle, TableStyle};
+use crate::utils::set_panic_hook;
+use std::collections::HashMap;
 use wasm_bindgen::prelude::wasm_bindgen;
-use wasm_bindgen_test::console_log;
+use wasm_bindgen::JsValue;
 
+mod callback_manager;
+mod camera;
 pub(crate) mod element_manager;
-mod mouse_state;
+mod js_table_content;
+mod pointer_state;
+
+// mouse_* から pointer_* を呼ぶ時の pointerId
+// ブラウザもマウスには 1 を割り当てる
+const MOUSE_POINTER_ID: i32 = 1;
+// ホイールの deltaY 1 あたりの拡大率（指数）
+const WHEEL_ZOOM_RATE: f64 = 0.002;
 
 #[wasm_bindgen]
 pub struct Binder {
     figures: Vec<Figure>,
-    mouse_state: MouseState,
+    // Figure の id から figures の index を引く
+    figure_indexes: HashMap<usize, usize>,
+    pointer_states: PointerStates,
     element_manager: ElementManager,
+    callback_manager: CallbackManager,
     pub(crate) has_update: bool,
     pub(crate) content_manager: ContentManager,
 }
 
 impl Binder {
+    // key ごとの TableContent が登録されていない表は、これから内容を取得する
     pub fn set_table_content_state(&mut self, table_content: Box<dyn TableContent>) {
         self.content_manager.table_content = Some(table_content);
     }
-}
-#[wasm_bindgen]
-impl Binder {
-    pub fn new() -> Binder {
+    // FigureBuilder::content_key が key の表は table_content から内容を取得する
+    pub fn set_table_content(&mut self, key: &str, table_content: Box<dyn TableContent>) {
+        self.content_manager
+            .keyed_table_contents
+            .insert(key.to_string(), table_content);
+    }
+    // key の表に thead と tbody をそのまま表示する
+    pub fn set_table_rows(&mut self, key: &str, thead: Vec<String>, tbody: Vec<Vec<String>>) {
+        self.set_table_content(
+            key,
+            Box::new(TableData {
+                thead,
+                tbody,
+                uses_templates: false,
+            }),
+        );
+    }
+    // set_table_rows と同じだが、値に書いた {path} などの式を評価して表示する
+    pub fn set_table_template_rows(
+        &mut self,
+        key: &str,
+        thead: Vec<String>,
+        tbody: Vec<Vec<String>>,
+    ) {
+        self.set_table_content(
+            key,
+            Box::new(TableData {
+                thead,
+                tbody,
+                uses_templates: true,
+            }),
+        );
+    }
+    // key の表の装飾を置き換える。when を解析できない場合は登録せずに理由を返す
+    pub fn set_table_style_value(
+        &mut self,
+        key: &str,
+        table_style: TableStyle,
+    ) -> Result<(), String> {
+        let table_style = CompiledTableStyle::new(table_style)?;
+        self.content_manager
+            .table_styles
+            .insert(key.to_string(), table_style);
+        Ok(())
+    }
+    // 表の値の {path} で参照できる値を保存する。次の update で表示に反映される
+    pub fn set_state_value(&mut self, path: &str, value: StateValue) {
+        self.content_manager.state_store.set(path, value);
+    }
+    // 指定した Figure の表の key に table_content を登録する
+    // 存在しない id や、表を持たない Figure の場合は false を返す
+    pub fn set_figure_table_content(
+        &mut self,
+        figure_id: usize,
+        table_content: Box<dyn TableContent>,
+    ) -> bool {
+        let Some(content_key) = self
+            .figure_indexes
+            .get(&figure_id)
+            .and_then(|index| self.figures[*index].content_key())
+            .map(|content_key| content_key.to_string())
+        else {
+            return false;
+        };
+        self.set_table_content(content_key.as_str(), table_content);
+        true
+    }
+    pub fn new_with_backend(backend: Box<dyn RenderBackend>) -> Binder {
         let mut binder = Binder {
             figures: vec![],
-            mouse_state: MouseState::new(),
-            element_manager: ElementManager::new("container"),
+            figure_indexes: HashMap::new(),
+            pointer_states: PointerStates::new(),
+            element_manager: ElementManager::new(backend),
+            callback_manager: CallbackManager::new(),
             has_update: false,
             content_manager: ContentManager {
                 table_content: None,
+                keyed_table_contents: HashMap::new(),
+                state_store: StateStore::default(),
+                table_styles: HashMap::new(),
             },
         };
         binder.initial_adjust();
         binder
     }
+    pub fn new_for_dev_with_backend(backend: Box<dyn RenderBackend>) -> Binder {
+        let mut binder = Binder::new_with_backend(backend);
+        let element_manager = &mut binder.element_manager;
+        let figures = vec![
+            Figure::new_window_dev(
+                "プレイヤー1",
+                100.0,
+                100.0,
+                "#333",
+                "status1",
+                element_manager,
+            ),
+            Figure::new_window_dev(
+                "プレイヤー2",
+                350.0,
+                100.0,
+                "#333",
+                "status2",
+                element_manager,
+            ),
+            Figure::new_log_window_dev("ゲームログ", 100.0, 650.0, "#333", "log", element_manager),
+        ];
+        for figure in figures {
+            binder.push_figure(figure);
+        }
+        binder.initial_adjust();
+        binder
+    }
+    // Figure が閉じられた時に、その Figure の id を受け取る
+    pub fn set_on_close_handler(&mut self, handler: Box<dyn Fn(usize)>) {
+        self.callback_manager.on_close = Some(handler);
+    }
+    pub fn set_on_hover_enter_handler(&mut self, handler: HoverHandler) {
+        self.callback_manager.on_hover_enter = Some(handler);
+    }
+    pub fn set_on_hover_leave_handler(&mut self, handler: HoverHandler) {
+        self.callback_manager.on_hover_leave = Some(handler);
+    }
+    pub fn set_on_template_error_handler(&mut self, handler: TemplateErrorHandler) {
+        self.callback_manager.on_template_error = Some(handler);
+    }
+}
+#[wasm_bindgen]
+impl Binder {
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Binder {
+        set_panic_hook();
+        Binder::new_with_backend(Box::new(WebSysBackend::new("container")))
+    }
     #[wasm_bindgen(constructor)]
     pub fn new_for_dev() -> Binder {
-        let mut element_manager = ElementManager::new("container");
-        let mut binder = Binder {
-            figures: vec![
-                Figure::new_window_dev(
-                    "プレイヤー1",
-                    100.0,
-                    100.0,
-                    "#333",
-                    "status1",
-                    &mut element_manager,
-                ),
-                Figure::new_window_dev(
-                    "プレイヤー2",
-                    350.0,
-                    100.0,
-                    "#333",
-                    "status2",
-                    &mut element_manager,
-                ),
-                Figure::new_log_window_dev(
-                    "ゲームログ",
-                    100.0,
-                    650.0,
-                    "#333",
-                    "log",
-                    &mut element_manager,
-                ),
-            ],
-            mouse_state: MouseState::new(),
-            element_manager,
-            has_update: false,
-            content_manager: ContentManager {
-                table_content: None,
+        set_panic_hook();
+        Binder::new_for_dev_with_backend(Box::new(WebSysBackend::new("container")))
+    }
+
+    // 追加した Figure の id を返す
+    pub fn add_figure(&mut self, figure_builder: &FigureBuilder) -> usize {
+        let figure = figure_builder.build(&mut self.element_manager);
+        let figure_id = figure.figure_id;
+        self.push_figure(figure);
+        self.initial_adjust_figure(self.figures.len() - 1);
+        self.has_update = true;
+        figure_id
+    }
+
+    // 指定した Figure を一番手前に表示する
+    pub fn bring_to_front(&mut self, figure_id: usize) {
+        if self.figure_indexes.contains_key(&figure_id) {
+            self.element_manager.re_append_figure(figure_id);
+        }
+    }
+
+    // Figure を削除する。存在しない id の場合は false を返す
+    pub fn remove_figure(&mut self, figure_id: usize) -> bool {
+        if !self.figure_indexes.contains_key(&figure_id) {
+            return false;
+        }
+        if matches!(self.pointer_states.hover, Some((hover_figure_id, _)) if hover_figure_id == figure_id)
+        {
+            self.set_hover(None);
+        }
+        let index = self.figure_indexes.remove(&figure_id).unwrap();
+        let figure = self.figures.remove(index);
+        for (figure_index, figure) in self.figures.iter().enumerate().skip(index) {
+            self.figure_indexes.insert(figure.figure_id, figure_index);
+        }
+        self.element_manager
+            .remove_figure_group(figure_id, &figure.element_indexes());
+        self.pointer_states
+            .pointers
+            .retain(|_, pointer| pointer.figure_id != Some(figure_id));
+        self.callback_manager.call_on_close(figure_id);
+        self.has_update = true;
+        true
+    }
+
+    // callback は閉じた Figure の id を引数に呼ばれる
+    pub fn set_on_close(&mut self, callback: js_sys::Function) {
+        self.set_on_close_handler(Box::new(move |figure_id| {
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from(figure_id as u32));
+        }));
+    }
+
+    // 作成時の大きさに戻す。存在しない id の場合は false を返す
+    pub fn reset_size(&mut self, figure_id: usize) -> bool {
+        let Some(index) = self.figure_indexes.get(&figure_id) else {
+            return false;
+        };
+        let figure = &mut self.figures[*index];
+        figure.reset_size();
+        figure.base_rect.clamp_position(&self.element_manager);
+        self.has_update = true;
+        true
+    }
+
+    // 内容の (x, y) px の位置が表示領域の左上に来るようにスクロールする
+    // NaN の軸は動かさず、f64::INFINITY（JS の Infinity）で末尾まで送る
+    pub fn scroll_to(&mut self, figure_id: usize, x: f64, y: f64) -> bool {
+        let Some(index) = self.figure_indexes.get(&figure_id) else {
+            return false;
+        };
+        let is_scrolled = self.figures[*index].scroll_to(&Point { x, y }, &self.element_manager);
+        if is_scrolled {
+            self.has_update = true;
+        }
+        is_scrolled
+    }
+
+    pub fn scroll_by(&mut self, figure_id: usize, delta_x: f64, delta_y: f64) -> bool {
+        let Some(index) = self.figure_indexes.get(&figure_id) else {
+            return false;
+        };
+        let is_scrolled = self.figures[*index].scroll_by(
+            &Point {
+                x: delta_x,
+                y: delta_y,
             },
+            &self.element_manager,
+        );
+        if is_scrolled {
+            self.has_update = true;
+        }
+        is_scrolled
+    }
+
+    // 末尾に追従する Figure で、遡っている間に増えた行数。それ以外の Figure では 0
+    pub fn unread_lines(&self, figure_id: usize) -> usize {
+        self.figure_indexes
+            .get(&figure_id)
+            .and_then(|index| self.figures[*index].unread_count())
+            .unwrap_or(0)
+    }
+
+    // ContentKind::AppendLog の Figure に行を追加する
+    pub fn push_line(&mut self, figure_id: usize, line: &str) -> bool {
+        let Some(index) = self.figure_indexes.get(&figure_id) else {
+            return false;
         };
-        binder.initial_adjust();
-        binder
+        let is_pushed = self.figures[*index].push_line(line);
+        if is_pushed {
+            self.has_update = true;
+        }
+        is_pushed
+    }
+
+    // 表示する内容が変わったことを知らせる。次の update で描き直す
+    pub fn invalidate(&mut self) {
+        self.has_update = true;
+    }
+
+    // callback は Figure の id と場所の名前（"title", "close-button", "resize-se" など）を引数に呼ばれる
+    pub fn set_on_hover_enter(&mut self, callback: js_sys::Function) {
+        self.set_on_hover_enter_handler(Box::new(move |figure_id, name| {
+            let _ = callback.call2(
+                &JsValue::NULL,
+                &JsValue::from(figure_id as u32),
+                &JsValue::from_str(name),
+            );
+        }));
+    }
+    pub fn set_on_hover_leave(&mut self, callback: js_sys::Function) {
+        self.set_on_hover_leave_handler(Box::new(move |figure_id, name| {
+            let _ = callback.call2(
+                &JsValue::NULL,
+                &JsValue::from(figure_id as u32),
+                &JsValue::from_str(name),
+            );
+        }));
+    }
+
+    // value は数値、文字列と、それらの配列。それ以外の場合は false を返す
+    pub fn set_state(&mut self, path: &str, value: JsValue) -> bool {
+        match StateValue::from_js(&value) {
+            Some(value) => {
+                self.set_state_value(path, value);
+                true
+            }
+            None => false,
+        }
+    }
+    // provider は getThead(key) と getTbody(key) を持つオブジェクトか、getTbody として呼ぶ関数
+    // どちらも文字列（数値なども可）の配列を返す。オブジェクトと関数以外の場合は false を返す
+    // provider の templates が true の時だけ、値の {path} などの式を評価する
+    pub fn set_table_provider(&mut self, key: &str, provider: JsValue) -> bool {
+        match JsTableContent::new(provider) {
+            Some(table_content) => {
+                self.set_table_content(key, Box::new(table_content));
+                true
+            }
+            None => false,
+        }
+    }
+    // key ごとの provider が登録されていない表に使う
+    pub fn set_default_table_provider(&mut self, provider: JsValue) -> bool {
+        match JsTableContent::new(provider) {
+            Some(table_content) => {
+                self.set_table_content_state(Box::new(table_content));
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn set_figure_table_provider(&mut self, figure_id: usize, provider: JsValue) -> bool {
+        match JsTableContent::new(provider) {
+            Some(table_content) => {
+                self.set_figure_table_content(figure_id, Box::new(table_content))
+            }
+            None => false,
+        }
+    }
+    // thead は文字列の配列（省略可）、tbody は文字列の配列の配列。形が違う場合は false を返す
+    pub fn set_table_data(&mut self, key: &str, thead: JsValue, tbody: JsValue) -> bool {
+        match table_data_from_js(&thead, &tbody) {
+            Some((thead, tbody)) => {
+                self.set_table_rows(key, thead, tbody);
+                true
+            }
+            None => false,
+        }
+    }
+    // set_table_data と同じだが、値に書いた {path} などの式を評価して表示する
+    pub fn set_table_template_data(&mut self, key: &str, thead: JsValue, tbody: JsValue) -> bool {
+        match table_data_from_js(&thead, &tbody) {
+            Some((thead, tbody)) => {
+                self.set_table_template_rows(key, thead, tbody);
+                true
+            }
+            None => false,
+        }
+    }
+    pub fn remove_table_content(&mut self, key: &str) -> bool {
+        self.content_manager
+            .keyed_table_contents
+            .remove(key)
+            .is_some()
+    }
+    // style は { stripe, rules: [{ row, column, when, fill, fontWeight, italic, background }] }
+    // 形が違う場合や when を解析できない場合は登録せずに理由を返す
+    pub fn set_table_style(&mut self, key: &str, style: JsValue) -> Result<(), String> {
+        let table_style = TableStyle::from_js(&style)?;
+        self.set_table_style_value(key, table_style)
+    }
+    pub fn remove_table_style(&mut self, key: &str) -> bool {
+        self.content_manager.table_styles.remove(key).is_some()
+    }
+    pub fn remove_state(&mut self, path: &str) -> bool {
+        self.content_manager.state_store.remove(path)
+    }
+    // 表の値に書く文字列を解析だけする。解析できない場合は位置と理由を返す
+    pub fn check_template(template: &str) -> Result<(), String> {
+        Template::parse(template)
+            .map(|_| ())
+            .map_err(|error| error.to_string())
+    }
+    // callback は解析できなかった表の値や、provider が投げた例外の場所と理由を 1 つの文字列で受け取る
+    pub fn set_on_template_error(&mut self, callback: js_sys::Function) {
+        self.set_on_template_error_handler(Box::new(move |message| {
+            let _ = callback.call1(&JsValue::NULL, &JsValue::from_str(message));
+        }));
     }
 
     pub fn set_dummy_state(&mut self) {
         self.content_manager.table_content = Some(Box::new(DummyState {}));
+        self.set_table_content("status2", Box::new(DummyRivalState {}));
+        self.set_state_value("rival.is_first", StateValue::Bool(true));
+        self.set_state_value("rival.hp", StateValue::Number(38.0));
+        self.set_state_value("rival.mhp", StateValue::Number(60.0));
     }
     pub fn update(&mut self) {
+        for figure in self.figures.iter_mut() {
+            if figure.repeat_paging() {
+                self.has_update = true;
+            }
+            // 値が変わったセルはここで書き換え済み。大きさが変わるかもしれないので測り直す
+            if figure.refresh_content(&mut self.element_manager, &self.content_manager) {
+                self.has_update = true;
+            }
+            for message in figure.take_template_errors() {
+                self.callback_manager
+                    .call_on_template_error(message.as_str());
+            }
+        }
         if self.has_update {
             self.adjust();
             self.has_update = false;
         }
+        for message in self.content_manager.take_errors() {
+            self.callback_manager
+                .call_on_template_error(message.as_str());
+        }
     }
     pub fn mouse_up(&mut self, raw_x: f64, raw_y: f64) {
-        if self.mouse_state.is_button_pushed {
-            let (x, y) = self.element_manager.get_internal_xy(raw_x, raw_y);
-            if let Some(found_figure) = self.figures.iter_mut().find(|figure| figure.is_pushed) {
-                found_figure.button_pressed(x, y, &self.element_manager);
-            }
-            self.mouse_state.is_button_pushed = false;
-        }
-        self.mouse_state.is_dragged = false;
-        self.update_base();
-        self.has_update = true;
+        self.pointer_up(MOUSE_POINTER_ID, raw_x, raw_y);
     }
-
     pub fn mouse_down(&mut self, raw_x: f64, raw_y: f64) {
+        self.pointer_down(MOUSE_POINTER_ID, "mouse", raw_x, raw_y, 0.5, true);
+    }
+    pub fn mouse_move(&mut self, raw_x: f64, raw_y: f64) {
+        self.pointer_move(MOUSE_POINTER_ID, "mouse", raw_x, raw_y, 0.0, true);
+    }
+
+    // PointerEvent の pointerId, pointerType, pressure, isPrimary をそのまま渡す
+    pub fn pointer_down(
+        &mut self,
+        pointer_id: i32,
+        pointer_type: &str,
+        raw_x: f64,
+        raw_y: f64,
+        pressure: f64,
+        is_primary: bool,
+    ) {
         let (x, y) = self.element_manager.get_internal_xy(raw_x, raw_y);
-        // mouse_down() => mouse_down() イベントを念の為抑制
-        if self.mouse_state.is_dragged {
-            self.mouse_up(raw_x, raw_y);
-        }
-        // 現状、一度につかめる Figure は一つだけ
-        // TODO
-        // element_manager 上の group_index と、binder の figures の index が一致しているという前提に基づいたロジック
-        // figure の作成順と g 要素登録順がズレるとバグる（そんなケースありますか？）
-        if let Some(group_index) = self
+        // pointer_down() => pointer_down() イベントを念の為抑制
+        if self.pointer_states.pointers.contains_key(&pointer_id) {
+            self.pointer_up(pointer_id, raw_x, raw_y);
+        }
+        let mut pointer_state =
+            PointerState::new(PointerType::from_name(pointer_type), pressure, is_primary);
+        // 手前の Figure が別のポインターにつかまれている時は何もしない
+        if let Some(index) = self
             .element_manager
             .figure_group_order
             .iter()
-            .find(|group_index| self.figures[**group_index].is_inner(x, y))
+            .map(|figure_id| self.figure_indexes[figure_id])
+            .find(|index| self.figures[*index].is_inner(x, y))
+            .filter(|index| {
+                !self
+                    .pointer_states
+                    .is_grabbing(self.figures[*index].figure_id)
+            })
         {
-            let found_figure = &mut self.figures[*group_index];
+            let found_figure = &mut self.figures[index];
             self.element_manager
-                .re_append_figure(found_figure.group_index);
+                .re_append_figure(found_figure.figure_id);
+            // 溝を押した時にページ送りするので、スクロールバーの長さを最新にしておく
+            found_figure.update_scroll_bars(&self.element_manager);
             if found_figure.grab(x, y) {
-                self.mouse_state.is_dragged = true;
-                self.mouse_state.drag_start_point = Point { x, y };
+                pointer_state.is_dragged = true;
+                pointer_state.drag_start_point = Point { x, y };
             }
             if found_figure.is_pushed {
-                self.mouse_state.is_button_pushed = true;
+                pointer_state.is_button_pushed = true;
+            }
+            if pointer_state.is_dragged || pointer_state.is_button_pushed {
+                pointer_state.figure_id = Some(found_figure.figure_id);
+            }
+        }
+        let (view_x, view_y) = self.element_manager.get_view_xy(raw_x, raw_y);
+        pointer_state.view_point = Point {
+            x: view_x,
+            y: view_y,
+        };
+        let is_free_touch =
+            pointer_state.pointer_type == PointerType::Touch && pointer_state.figure_id.is_none();
+        self.pointer_states
+            .pointers
+            .insert(pointer_id, pointer_state);
+        // 背景に二本目の指が置かれたらピンチを始める
+        if is_free_touch && self.pointer_states.pinch.is_none() {
+            if let Some(other_id) = self.pointer_states.free_touch_id(pointer_id) {
+                let pointers = &self.pointer_states.pointers;
+                self.pointer_states.pinch = Some(PinchState::new(
+                    (other_id, pointer_id),
+                    (
+                        &pointers[&other_id].view_point,
+                        &pointers[&pointer_id].view_point,
+                    ),
+                    &self.element_manager.camera,
+                ));
             }
         }
         self.has_update = true;
     }
 
-    pub fn mouse_move(&mut self, raw_x: f64, raw_y: f64) {
+    pub fn pointer_move(
+        &mut self,
+        pointer_id: i32,
+        pointer_type: &str,
+        raw_x: f64,
+        raw_y: f64,
+        pressure: f64,
+        is_primary: bool,
+    ) {
         let (x, y) = self.element_manager.get_internal_xy(raw_x, raw_y);
-        if !self.mouse_state.is_dragged {
+        let (view_x, view_y) = self.element_manager.get_view_xy(raw_x, raw_y);
+        if let Some(pointer_state) = self.pointer_states.pointers.get_mut(&pointer_id) {
+            pointer_state.view_point = Point {
+                x: view_x,
+                y: view_y,
+            };
+            pointer_state.pressure = pressure;
+        }
+        if let Some(pinch) = self
+            .pointer_states
+            .pinch
+            .as_ref()
+            .filter(|pinch| pinch.contains(pointer_id))
+        {
+            let pointers = &self.pointer_states.pointers;
+            let (first, second) = pinch.pointer_ids;
+            self.element_manager.camera =
+                pinch.camera((&pointers[&first].view_point, &pointers[&second].view_point));
+            self.has_update = true;
             return;
         }
+        let Some(pointer_state) = self
+            .pointer_states
+            .pointers
+            .get_mut(&pointer_id)
+            .filter(|pointer_state| pointer_state.figure_id.is_some())
+        else {
+            if PointerState::can_hover(PointerType::from_name(pointer_type), is_primary) {
+                self.update_hover(x, y);
+            }
+            return;
+        };
+        let figure_id = pointer_state.figure_id.unwrap();
         let delta_point = Point {
-            x: x - self.mouse_state.drag_start_point.x,
-            y: y - self.mouse_state.drag_start_point.y,
+            x: x - pointer_state.drag_start_point.x,
+            y: y - pointer_state.drag_start_point.y,
         };
-        for figure in self.figures.iter_mut() {
-            figure.move_xy(
-                &self.mouse_state.drag_start_point,
-                &delta_point,
-                &self.element_manager,
-            );
+        let figure = &mut self.figures[self.figure_indexes[&figure_id]];
+        figure.move_xy(
+            &pointer_state.drag_start_point,
+            &delta_point,
+            &self.element_manager,
+        );
+        self.has_update = true;
+    }
+
+    pub fn pointer_up(&mut self, pointer_id: i32, raw_x: f64, raw_y: f64) {
+        let (x, y) = self.element_manager.get_internal_xy(raw_x, raw_y);
+        let Some(pointer_state) = self.pointer_states.remove(pointer_id) else {
+            return;
+        };
+        if let Some(figure_id) = pointer_state.figure_id {
+            let figure = &mut self.figures[self.figure_indexes[&figure_id]];
+            if pointer_state.is_button_pushed {
+                figure.button_pressed(x, y, &mut self.element_manager);
+            }
+            figure.update_base();
+            if figure.is_close_requested {
+                self.remove_figure(figure_id);
+            }
+        }
+        if PointerState::can_hover(pointer_state.pointer_type, pointer_state.is_primary) {
+            self.update_hover(x, y);
+        }
+        self.has_update = true;
+    }
+
+    // ボタンは押さずに、つかんでいた Figure を離す
+    pub fn pointer_cancel(&mut self, pointer_id: i32) {
+        let Some(pointer_state) = self.pointer_states.remove(pointer_id) else {
+            return;
+        };
+        if let Some(figure_id) = pointer_state.figure_id {
+            let figure = &mut self.figures[self.figure_indexes[&figure_id]];
+            figure.release_push();
+            figure.update_base();
+        }
+        self.has_update = true;
+    }
+
+    // WheelEvent の deltaX, deltaY, deltaMode, shiftKey をそのまま渡す
+    // ポインターの下の Scrollable をスクロールした時は true（JS 側で preventDefault する）
+    pub fn wheel(
+        &mut self,
+        raw_x: f64,
+        raw_y: f64,
+        delta_x: f64,
+        delta_y: f64,
+        delta_mode: u32,
+        shift_key: bool,
+    ) -> bool {
+        let (x, y) = self.element_manager.get_internal_xy(raw_x, raw_y);
+        // shift を押している時は縦方向の回転を横方向のスクロールとして扱う
+        let delta = if shift_key && delta_x == 0.0 {
+            Point { x: delta_y, y: 0.0 }
+        } else {
+            Point {
+                x: delta_x,
+                y: delta_y,
+            }
+        };
+        let Some(index) = self
+            .element_manager
+            .figure_group_order
+            .iter()
+            .map(|figure_id| self.figure_indexes[figure_id])
+            .find(|index| self.figures[*index].is_inner(x, y))
+        else {
+            return false;
+        };
+        let is_scrolled = self.figures[index].wheel(
+            x,
+            y,
+            &delta,
+            DeltaMode::from_value(delta_mode),
+            &self.element_manager,
+        );
+        if is_scrolled {
+            self.has_update = true;
         }
+        is_scrolled
+    }
+
+    // ctrl を押しながらのホイール。ポインターの位置を中心に拡大縮小する
+    pub fn wheel_zoom(&mut self, raw_x: f64, raw_y: f64, delta_y: f64) {
+        let (view_x, view_y) = self.element_manager.get_view_xy(raw_x, raw_y);
+        let camera = &mut self.element_manager.camera;
+        let zoom = camera.zoom * (-delta_y * WHEEL_ZOOM_RATE).exp();
+        camera.zoom_at(
+            &Point {
+                x: view_x,
+                y: view_y,
+            },
+            zoom,
+        );
+        self.has_update = true;
+    }
+    // view = desktop * zoom + (pan_x, pan_y)
+    pub fn set_camera(&mut self, pan_x: f64, pan_y: f64, zoom: f64) {
+        let camera = &mut self.element_manager.camera;
+        camera.pan_x = pan_x;
+        camera.pan_y = pan_y;
+        camera.set_zoom(zoom);
         self.has_update = true;
     }
+    pub fn reset_camera(&mut self) {
+        self.element_manager.camera = Camera::new();
+        self.has_update = true;
+    }
+
+    // 押されていないポインターの場合は None
+    pub fn pointer_pressure(&self, pointer_id: i32) -> Option<f64> {
+        self.pointer_states
+            .pointers
+            .get(&pointer_id)
+            .map(|pointer_state| pointer_state.pressure)
+    }
 
     pub fn set_ref_points(&mut self, offset_x: f64, offset_y: f64, max_y: f64) {
         self.element_manager.offset_x = offset_x;
         self.element_manager.offset_y = offset_y;
-        // TODO
-        // パラメータ化
-        self.element_manager.scale = (max_y - offset_y) / 800.0;
+        self.element_manager.scale = (max_y - offset_y) / self.element_manager.container_height;
+        for figure in self.figures.iter_mut() {
+            figure.base_rect.clamp_position(&self.element_manager);
+        }
+        self.has_update = true;
     }
 }
 
 impl Binder {
+    fn push_figure(&mut self, figure: Figure) {
+        self.figure_indexes
+            .insert(figure.figure_id, self.figures.len());
+        self.figures.push(figure);
+    }
     pub(crate) fn adjust(&mut self) {
         for figure in self.figures.iter_mut() {
-            figure.adjust(&self.element_manager);
+            figure.adjust(&mut self.element_manager);
             figure.base_rect.adjust(&mut self.element_manager);
             for part_rect in figure.parts.iter_mut() {
-                part_rect.adjust(
-                    &figure.base_rect,
-                    &mut self.element_manager,
-                    &mut self.content_manager,
-                );
+                part_rect.adjust(&figure.base_rect, &mut self.element_manager);
             }
+            figure.adjust_jump_to_end(&mut self.element_manager);
         }
     }
     pub(crate) fn initial_adjust(&mut self) {
-        for figure in self.figures.iter_mut() {
-            figure.base_rect.initial_adjust(&self.element_manager);
-            for part_rect in figure.parts.iter_mut() {
-                part_rect.adjust(
-                    &figure.base_rect,
-                    &mut self.element_manager,
-                    &self.content_manager,
-                );
-            }
-            figure.adjust(&self.element_manager);
+        for index in 0..self.figures.len() {
+            self.initial_adjust_figure(index);
         }
         self.adjust();
     }
+    fn initial_adjust_figure(&mut self, index: usize) {
+        let figure = &mut self.figures[index];
+        // 内容に合わせた大きさにするため、最初の 1 回だけは adjust の前に内容を取得する
+        figure.refresh_content(&mut self.element_manager, &self.content_manager);
+        figure.base_rect.initial_adjust(&mut self.element_manager);
+        for part_rect in figure.parts.iter_mut() {
+            part_rect.adjust(&figure.base_rect, &mut self.element_manager);
+        }
+        figure.adjust_jump_to_end(&mut self.element_manager);
+        figure.adjust(&mut self.element_manager);
+    }
 
-    pub(crate) fn update_base(&mut self) {
-        for figure in self.figures.iter_mut() {
-            figure.update_base();
+    // 手前の Figure から順に、マウスポインターが乗っている場所を探す
+    fn update_hover(&mut self, x: f64, y: f64) {
+        let hover = self
+            .element_manager
+            .figure_group_order
+            .iter()
+            .map(|figure_id| &self.figures[self.figure_indexes[figure_id]])
+            .find_map(|figure| {
+                figure
+                    .hover_target(x, y)
+                    .map(|target| (figure.figure_id, target))
+            });
+        if hover != self.pointer_states.hover {
+            self.set_hover(hover);
         }
     }
+    fn set_hover(&mut self, hover: Option<(usize, HoverTarget)>) {
+        if let Some((figure_id, target)) = self.pointer_states.hover.take() {
+            let figure = &self.figures[self.figure_indexes[&figure_id]];
+            figure.set_hover_style(target, false, &mut self.element_manager);
+            self.callback_manager
+                .call_on_hover_leave(figure_id, figure.hover_name(target).as_str());
+        }
+        let container = self.element_manager.get_container();
+        if let Some((figure_id, target)) = hover {
+            let figure = &self.figures[self.figure_indexes[&figure_id]];
+            figure.set_hover_style(target, true, &mut self.element_manager);
+            self.element_manager.backend.set_attribute(
+                container,
+                "style",
+                format!("cursor: {};", figure.hover_cursor(target)).as_str(),
+            );
+            self.callback_manager
+                .call_on_hover_enter(figure_id, figure.hover_name(target).as_str());
+        } else {
+            self.element_manager
+                .backend
+                .remove_attribute(container, "style");
+        }
+        self.pointer_states.hover = hover;
+    }
 }
 
 pub struct ContentManager {
     pub(crate) table_content: Option<Box<dyn TableContent>>,
+    pub(crate) keyed_table_contents: HashMap<String, Box<dyn TableContent>>,
+    // 表の値の {path} から参照する
+    pub(crate) state_store: StateStore,
+    // key ごとの表の装飾
+    pub(crate) table_styles: HashMap<String, CompiledTableStyle>,
 }
 
 impl ContentManager {
+    // key で登録されたものを優先する
+    fn find_table_content(&self, key: &str) -> Option<&dyn TableContent> {
+        self.keyed_table_contents
+            .get(key)
+            .or(self.table_content.as_ref())
+            .map(|content| content.as_ref())
+    }
+    pub(crate) fn get_thead(&self, key: &str) -> Option<Vec<String>> {
+        self.find_table_content(key)
+            .map(|content| content.get_thead(key))
+    }
     pub(crate) fn get_tbody(&self, key: &str) -> Option<Vec<Vec<String>>> {
-        if let Some(content) = &self.table_content {
-            Some(content.get_tbody(key))
-        } else {
-            None
-        }
+        self.find_table_content(key)
+            .map(|content| content.get_tbody(key))
+    }
+    pub(crate) fn uses_templates(&self, key: &str) -> bool {
+        self.find_table_content(key)
+            .is_some_and(|content| content.uses_templates())
+    }
+    fn take_errors(&self) -> Vec<String> {
+        self.table_content
+            .iter()
+            .chain(self.keyed_table_contents.values())
+            .flat_map(|content| content.take_errors())
+            .collect()
     }
 }
 pub trait TableContent {
-    fn get_thead(&self, key: &str) -> Vec<String> {
-        match key {
-            _ => vec![],
-        }
+    fn get_thead(&self, _key: &str) -> Vec<String> {
+        vec![]
     }
-    fn get_tbody(&self, key: &str) -> Vec<Vec<String>> {
-        match key {
-            _ => vec![
-                vec![String::from("行動順"), String::from("後攻")],
-                vec![String::from("HP/MHP"), String::from("50/50")],
-                vec![String::from("被ダメ"), String::from("5")],
-            ],
-        }
+    fn get_tbody(&self, _key: &str) -> Vec<Vec<String>> {
+        vec![
+            vec![String::from("行動順"), String::from("後攻")],
+            vec![String::from("HP/MHP"), String::from("50/50")],
+            vec![String::from("被ダメ"), String::from("5")],
+        ]
+    }
+    // true の時は値の {} を式として評価する。既定では値をそのまま表示する
+    fn uses_templates(&self) -> bool {
+        false
+    }
+    // 内容を取得できなかった理由。Binder::update で取り出して set_on_template_error に通知する
+    fn take_errors(&self) -> Vec<String> {
+        vec![]
     }
 }
 
 struct DummyState {}
 
 impl TableContent for DummyState {}
+
+// プレイヤー2 の表だけ別の値を表示する。HP は state から取り出す
+struct DummyRivalState {}
+
+impl TableContent for DummyRivalState {
+    fn get_tbody(&self, _key: &str) -> Vec<Vec<String>> {
+        vec![
+            vec![
+                String::from("行動順"),
+                String::from("{rival.is_first ? '先攻' : '後攻'}"),
+            ],
+            vec![
+                String::from("HP/MHP"),
+                String::from("{rival.hp}/{rival.mhp}"),
+            ],
+            vec![String::from("被ダメ"), String::from("22")],
+        ]
+    }
+    fn uses_templates(&self) -> bool {
+        true
+    }
+}
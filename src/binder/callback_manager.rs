@@ -0,0 +1,44 @@
+// Figure の id と、Figure 内の場所の名前（"title", "close-button" など）を受け取る
+pub(crate) type HoverHandler = Box<dyn Fn(usize, &str)>;
+// 解析できなかった表の値の場所と理由を受け取る
+pub(crate) type TemplateErrorHandler = Box<dyn Fn(&str)>;
+
+// JS（テストでは Rust）に通知するコールバックの置き場所
+pub(crate) struct CallbackManager {
+    pub(crate) on_close: Option<Box<dyn Fn(usize)>>,
+    pub(crate) on_hover_enter: Option<HoverHandler>,
+    pub(crate) on_hover_leave: Option<HoverHandler>,
+    // 表の値を解析できなかった時の説明を受け取る
+    pub(crate) on_template_error: Option<TemplateErrorHandler>,
+}
+
+impl CallbackManager {
+    pub(crate) fn new() -> CallbackManager {
+        CallbackManager {
+            on_close: None,
+            on_hover_enter: None,
+            on_hover_leave: None,
+            on_template_error: None,
+        }
+    }
+    pub(crate) fn call_on_close(&self, figure_id: usize) {
+        if let Some(on_close) = self.on_close.as_ref() {
+            on_close(figure_id);
+        }
+    }
+    pub(crate) fn call_on_hover_enter(&self, figure_id: usize, name: &str) {
+        if let Some(on_hover_enter) = self.on_hover_enter.as_ref() {
+            on_hover_enter(figure_id, name);
+        }
+    }
+    pub(crate) fn call_on_template_error(&self, message: &str) {
+        if let Some(on_template_error) = self.on_template_error.as_ref() {
+            on_template_error(message);
+        }
+    }
+    pub(crate) fn call_on_hover_leave(&self, figure_id: usize, name: &str) {
+        if let Some(on_hover_leave) = self.on_hover_leave.as_ref() {
+            on_hover_leave(figure_id, name);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use crate::math::Point;
+
+// set_ref_points で決まる座標系（view）の上に重ねる、デスクトップ全体の移動と拡大縮小
+// view = desktop * zoom + pan
+#[derive(Clone, Debug)]
+pub(crate) struct Camera {
+    pub(crate) pan_x: f64,
+    pub(crate) pan_y: f64,
+    pub(crate) zoom: f64,
+}
+
+impl Camera {
+    const MIN_ZOOM: f64 = 0.25;
+    const MAX_ZOOM: f64 = 4.0;
+    pub(crate) fn new() -> Camera {
+        Camera {
+            pan_x: 0.0,
+            pan_y: 0.0,
+            zoom: 1.0,
+        }
+    }
+    pub(crate) fn to_desktop(&self, view_x: f64, view_y: f64) -> (f64, f64) {
+        (
+            (view_x - self.pan_x) / self.zoom,
+            (view_y - self.pan_y) / self.zoom,
+        )
+    }
+    pub(crate) fn set_zoom(&mut self, zoom: f64) {
+        self.zoom = zoom.clamp(Camera::MIN_ZOOM, Camera::MAX_ZOOM);
+    }
+    // view 上の center に映っているデスクトップの点を動かさずに拡大縮小する
+    pub(crate) fn zoom_at(&mut self, center: &Point, zoom: f64) {
+        let (desktop_x, desktop_y) = self.to_desktop(center.x, center.y);
+        self.set_zoom(zoom);
+        self.pan_x = center.x - desktop_x * self.zoom;
+        self.pan_y = center.y - desktop_y * self.zoom;
+    }
+    // Figure の g 要素の transform
+    // 初期状態では translate だけにして、これまでと同じ出力にする
+    pub(crate) fn figure_transform(&self, x: f64, y: f64) -> String {
+        let view_x = x * self.zoom + self.pan_x;
+        let view_y = y * self.zoom + self.pan_y;
+        if self.zoom == 1.0 {
+            format!("translate({}, {})", view_x, view_y)
+        } else {
+            format!("translate({}, {}) scale({})", view_x, view_y, self.zoom)
+        }
+    }
+}
+
+// 二本指でのピンチと移動
+// 始めた時点のカメラを基準にして、指の中点と間隔の変化を反映する
+pub(crate) struct PinchState {
+    pub(crate) pointer_ids: (i32, i32),
+    pub(crate) start_center: Point,
+    pub(crate) start_distance: f64,
+    pub(crate) start_camera: Camera,
+}
+
+impl PinchState {
+    pub(crate) fn new(
+        pointer_ids: (i32, i32),
+        points: (&Point, &Point),
+        camera: &Camera,
+    ) -> PinchState {
+        let (start_center, start_distance) = PinchState::center_and_distance(points);
+        PinchState {
+            pointer_ids,
+            start_center,
+            start_distance,
+            start_camera: camera.clone(),
+        }
+    }
+    pub(crate) fn contains(&self, pointer_id: i32) -> bool {
+        self.pointer_ids.0 == pointer_id || self.pointer_ids.1 == pointer_id
+    }
+    fn center_and_distance((first, second): (&Point, &Point)) -> (Point, f64) {
+        (
+            Point {
+                x: (first.x + second.x) / 2.0,
+                y: (first.y + second.y) / 2.0,
+            },
+            (first.x - second.x).hypot(first.y - second.y),
+        )
+    }
+    pub(crate) fn camera(&self, points: (&Point, &Point)) -> Camera {
+        let (center, distance) = PinchState::center_and_distance(points);
+        let mut camera = self.start_camera.clone();
+        if self.start_distance > 0.0 {
+            camera.zoom_at(
+                &self.start_center,
+                camera.zoom * distance / self.start_distance,
+            );
+        }
+        camera.pan_x += center.x - self.start_center.x;
+        camera.pan_y += center.y - self.start_center.y;
+        camera
+    }
+}
@@ -1,138 +1,132 @@
-use web_sys::{window, Document, Element};
+use crate::binder::camera::Camera;
+use crate::render_backend::{NodeId, RenderBackend};
+use std::collections::BTreeMap;
+
 pub(crate) struct ElementManager {
-    pub(crate) document: Document,
-    pub(crate) container_id: String,
-    pub(crate) elements: Vec<Element>,
+    pub(crate) backend: Box<dyn RenderBackend>,
+    // Figure の削除で空いた index は次に作る要素で再利用する
+    elements: Vec<Option<NodeId>>,
     pub(crate) offset_x: f64,
     pub(crate) offset_y: f64,
     pub(crate) scale: f64,
-    pub(crate) figure_groups: Vec<Element>,
+    // svg の viewBox と同じ内部座標系の大きさ
+    pub(crate) container_width: f64,
+    pub(crate) container_height: f64,
+    pub(crate) camera: Camera,
+    // Figure の id と、その Figure の g 要素
+    pub(crate) figure_groups: BTreeMap<usize, NodeId>,
+    // Figure の id を手前に表示されている順に並べたもの
     pub(crate) figure_group_order: Vec<usize>,
+    next_figure_id: usize,
 }
 
 impl ElementManager {
-    pub(crate) fn get_container(&self) -> Element {
-        self.document
-            .get_element_by_id(self.container_id.as_str())
-            .unwrap()
+    pub(crate) fn get_container(&self) -> NodeId {
+        self.backend.container()
     }
-    pub(crate) fn new(container_id: &str) -> ElementManager {
+    pub(crate) fn new(backend: Box<dyn RenderBackend>) -> ElementManager {
         ElementManager {
-            document: window().unwrap().document().unwrap(),
-            container_id: container_id.to_string(),
+            backend,
             elements: vec![],
             offset_x: 0.0,
             offset_y: 0.0,
             scale: 1.0,
-            figure_groups: vec![],
+            container_width: 1200.0,
+            container_height: 800.0,
+            camera: Camera::new(),
+            figure_groups: BTreeMap::new(),
             figure_group_order: vec![],
+            next_figure_id: 0,
         }
     }
 
-    pub(crate) fn re_append_figure(&mut self, group_index: usize) {
-        let temporary_id = "temporary_id_re_append_figure";
-        self.figure_groups[group_index].set_id(temporary_id);
-        if let Some((index, _)) = self
+    pub(crate) fn re_append_figure(&mut self, figure_id: usize) {
+        if let Some(index) = self
             .figure_group_order
             .iter()
-            .enumerate()
-            .find(|(_, value)| **value == group_index)
+            .position(|value| *value == figure_id)
         {
             self.figure_group_order.remove(index);
-            self.figure_group_order.insert(0, group_index);
+            self.figure_group_order.insert(0, figure_id);
         };
-        let element = self.document.get_element_by_id(temporary_id).unwrap();
-        element.remove_attribute("id").unwrap();
-        self.get_container().append_child(&*element).unwrap();
-    }
-    pub(crate) fn create_figure_group(&mut self, container: &Element) -> usize {
-        let group = self
-            .document
-            .create_element_ns(Option::from("http://www.w3.org/2000/svg"), "g")
-            .unwrap();
-        container.append_child(&*group).unwrap();
-        self.figure_groups.push(group);
-        let group_index = self.figure_groups.len() - 1;
-        self.figure_group_order.push(group_index);
-        group_index
+        let container = self.get_container();
+        self.backend
+            .append_child(container, self.figure_groups[&figure_id]);
     }
-
-    pub(crate) fn create_element(&mut self, container: &Element) -> usize {
-        let rect = self
-            .document
-            .create_element_ns(Option::from("http://www.w3.org/2000/svg"), "rect")
-            .unwrap();
-        container.append_child(&*rect).unwrap();
-        self.elements.push(rect);
-        self.elements.len() - 1
+    // 後から追加した g 要素ほど手前に表示されるので、figure_group_order の先頭に入れる
+    pub(crate) fn create_figure_group(&mut self, container: NodeId) -> usize {
+        let group = self.backend.create_element(container, "g");
+        let figure_id = self.next_figure_id;
+        self.next_figure_id += 1;
+        self.figure_groups.insert(figure_id, group);
+        self.figure_group_order.insert(0, figure_id);
+        figure_id
     }
 
-    pub(crate) fn create_element_with_defs_id(&mut self, container: &Element, id: &str) -> usize {
-        // TODO
-        // get_element_by_idして、違う親に append_child すると、それは要素の移動になる！！！
-        // (元のJSからそう…）
-        container
-            .append_child(
-                &self
-                    .document
-                    .get_element_by_id(id)
-                    .unwrap()
-                    .clone_node()
-                    .unwrap(),
-            )
-            .unwrap();
-        let children = container.children();
-        let children_length = children.length();
-        let element = children.item(children_length - 1).unwrap();
-        element.remove_attribute("id").unwrap();
-        self.elements.push(element);
-        self.elements.len() - 1
+    pub(crate) fn create_element_with_defs_id(&mut self, container: NodeId, id: &str) -> usize {
+        let element = self.backend.clone_template(container, id);
+        self.register_element(element)
     }
 
-    pub(crate) fn create_element_with_symbol_id(&mut self, container: &Element, id: &str) -> usize {
-        let symbol = self.document.get_element_by_id(id).unwrap();
-        let symbol_children = symbol.children();
-        let symbol_children_length = symbol_children.length();
-        for n in 0..symbol_children_length {
-            // 要素の clone (deep copy) は Node 単位でしかできない
-            // 変換後は Node になるが、child_nodes() だと 空白Node もコピーすることになるので children() を使う
-            let element = symbol_children.item(n).unwrap();
-            // clone_node() では、孫要素は clone されないので、先に inner_html を取得しておく
-            let inner_html = element.inner_html();
-            let element_node = element.clone_node().unwrap();
-            container.append_child(&element_node).unwrap();
-            // symbol 配下の最初の要素を rect とみなす暗黙ルール…
-            let container_children = container.children();
-            let container_children_length = container_children.length();
-            let copied_element = container_children
-                .item(container_children_length - 1)
-                .unwrap();
-            // Node として親へ append_child したあとに Element として再取得ができているので inner_html 書き戻し
-            copied_element.set_inner_html(inner_html.as_str());
-            if n == 0 {
-                self.elements.push(copied_element);
-            }
+    pub(crate) fn create_element_with_symbol_id(&mut self, container: NodeId, id: &str) -> usize {
+        let copied_elements = self.backend.clone_symbol_children(container, id);
+        // symbol 配下の最初の要素を rect とみなす暗黙ルール…
+        self.register_element(copied_elements[0])
+    }
+    pub(crate) fn create_element(&mut self, container: NodeId, tag_name: &str) -> usize {
+        let element = self.backend.create_element(container, tag_name);
+        self.register_element(element)
+    }
+    pub(crate) fn create_element_with_group(&mut self, container: NodeId) -> usize {
+        let rect = self.backend.create_element(container, "rect");
+        self.backend.create_element(container, "g");
+        self.register_element(rect)
+    }
+    fn register_element(&mut self, element: NodeId) -> usize {
+        if let Some(index) = self.elements.iter().position(|element| element.is_none()) {
+            self.elements[index] = Some(element);
+            index
+        } else {
+            self.elements.push(Some(element));
+            self.elements.len() - 1
         }
-        self.elements.len() - 1
-    }
-    pub(crate) fn create_element_with_group(&mut self, container: &Element) -> usize {
-        let rect = self
-            .document
-            .create_element_ns(Option::from("http://www.w3.org/2000/svg"), "rect")
-            .unwrap();
-        container.append_child(&*rect).unwrap();
-        let g = self
-            .document
-            .create_element_ns(Option::from("http://www.w3.org/2000/svg"), "g")
-            .unwrap();
-        container.append_child(&*g).unwrap();
-        self.elements.push(rect);
-        self.elements.len() - 1
     }
-    pub(crate) fn get_internal_xy(&self, x: f64, y: f64) -> (f64, f64) {
+    pub(crate) fn element(&self, element_index: usize) -> NodeId {
+        self.elements[element_index].unwrap()
+    }
+    // g 要素ごと削除し、配下の要素の index を解放する
+    pub(crate) fn remove_figure_group(&mut self, figure_id: usize, element_indexes: &[usize]) {
+        if let Some(group) = self.figure_groups.remove(&figure_id) {
+            self.backend.remove(group);
+        }
+        self.figure_group_order.retain(|value| *value != figure_id);
+        for element_index in element_indexes {
+            self.elements[*element_index] = None;
+        }
+    }
+    pub(crate) fn set_attribute(&mut self, element_index: usize, name: &str, value: &str) {
+        self.backend
+            .set_attribute(self.element(element_index), name, value);
+    }
+    pub(crate) fn get_attribute(&self, element_index: usize, name: &str) -> Option<String> {
+        self.backend
+            .get_attribute(self.element(element_index), name)
+    }
+    // element_index の要素の直後にある g 要素（content 用のグループ）
+    pub(crate) fn get_sibling_group(&self, element_index: usize) -> Option<NodeId> {
+        self.backend
+            .next_sibling(self.element(element_index))
+            .filter(|sibling| self.backend.tag_name(*sibling) == "g")
+    }
+    // カメラを通す前の座標。ピンチの判定に使う
+    pub(crate) fn get_view_xy(&self, x: f64, y: f64) -> (f64, f64) {
         (
             (x - self.offset_x) / self.scale,
             (y - self.offset_y) / self.scale,
         )
     }
+    pub(crate) fn get_internal_xy(&self, x: f64, y: f64) -> (f64, f64) {
+        let (view_x, view_y) = self.get_view_xy(x, y);
+        self.camera.to_desktop(view_x, view_y)
+    }
 }
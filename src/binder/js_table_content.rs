@@ -0,0 +1,145 @@
+use crate::binder::TableContent;
+use crate::state_store::StateValue;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use wasm_bindgen::{JsCast, JsValue};
+
+// Binder::set_table_data で渡された値をそのまま返す
+pub(crate) struct TableData {
+    pub(crate) thead: Vec<String>,
+    pub(crate) tbody: Vec<Vec<String>>,
+    pub(crate) uses_templates: bool,
+}
+
+impl TableContent for TableData {
+    fn get_thead(&self, _key: &str) -> Vec<String> {
+        self.thead.clone()
+    }
+    fn get_tbody(&self, _key: &str) -> Vec<Vec<String>> {
+        self.tbody.clone()
+    }
+    fn uses_templates(&self) -> bool {
+        self.uses_templates
+    }
+}
+
+// JS のオブジェクトの getThead(key) と getTbody(key) を呼んで内容を取得する
+// 関数を渡した場合は getTbody として呼ぶ。templates が true なら値の {} を式として評価する
+pub(crate) struct JsTableContent {
+    provider: JsValue,
+    uses_templates: bool,
+    // 投げられた例外。Binder::update で取り出して通知する
+    errors: RefCell<Vec<String>>,
+    // (key, メソッド名) ごとの直前の例外。同じ例外が続く間は通知しない
+    last_errors: RefCell<HashMap<(String, &'static str), String>>,
+}
+
+impl JsTableContent {
+    pub(crate) fn new(provider: JsValue) -> Option<JsTableContent> {
+        if !provider.is_object() && !provider.is_function() {
+            return None;
+        }
+        let uses_templates = js_sys::Reflect::get(&provider, &JsValue::from_str("templates"))
+            .is_ok_and(|templates| templates.is_truthy());
+        Some(JsTableContent {
+            provider,
+            uses_templates,
+            errors: RefCell::new(vec![]),
+            last_errors: RefCell::new(HashMap::new()),
+        })
+    }
+    // メソッドが無い時や例外が投げられた時は None
+    fn call(&self, name: &'static str, key: &str) -> Option<JsValue> {
+        let function = if self.provider.is_function() {
+            if name != "getTbody" {
+                return None;
+            }
+            self.provider.clone()
+        } else {
+            js_sys::Reflect::get(&self.provider, &JsValue::from_str(name)).ok()?
+        };
+        let result = function
+            .dyn_ref::<js_sys::Function>()?
+            .call1(&self.provider, &JsValue::from_str(key));
+        let mut last_errors = self.last_errors.borrow_mut();
+        let last_error_key = (key.to_string(), name);
+        match result {
+            Ok(value) => {
+                last_errors.remove(&last_error_key);
+                Some(value)
+            }
+            Err(error) => {
+                let message = format!("{} の {}: {}", key, name, error_message(&error));
+                if last_errors.get(&last_error_key) != Some(&message) {
+                    self.errors.borrow_mut().push(message.clone());
+                    last_errors.insert(last_error_key, message);
+                }
+                None
+            }
+        }
+    }
+}
+
+impl TableContent for JsTableContent {
+    fn get_thead(&self, key: &str) -> Vec<String> {
+        self.call("getThead", key)
+            .and_then(|value| strings_from_js(&value))
+            .unwrap_or_default()
+    }
+    fn get_tbody(&self, key: &str) -> Vec<Vec<String>> {
+        self.call("getTbody", key)
+            .and_then(|value| rows_from_js(&value))
+            .unwrap_or_default()
+    }
+    fn uses_templates(&self) -> bool {
+        self.uses_templates
+    }
+    fn take_errors(&self) -> Vec<String> {
+        self.errors.take()
+    }
+}
+
+// Error なら message、それ以外は文字列にした値
+fn error_message(error: &JsValue) -> String {
+    match error.dyn_ref::<js_sys::Error>() {
+        Some(error) => String::from(error.message()),
+        None => error.as_string().unwrap_or_else(|| format!("{:?}", error)),
+    }
+}
+
+// 配列の要素を文字列にする。数値や真偽値は set_state と同じ表記、null などは空文字
+pub(crate) fn strings_from_js(value: &JsValue) -> Option<Vec<String>> {
+    let array = value.dyn_ref::<js_sys::Array>()?;
+    Some(
+        array
+            .iter()
+            .map(|item| {
+                StateValue::from_js(&item)
+                    .map(|item| item.to_string())
+                    .unwrap_or_default()
+            })
+            .collect(),
+    )
+}
+
+// thead は省略できる
+pub(crate) fn table_data_from_js(
+    thead: &JsValue,
+    tbody: &JsValue,
+) -> Option<(Vec<String>, Vec<Vec<String>>)> {
+    let thead = if thead.is_undefined() || thead.is_null() {
+        vec![]
+    } else {
+        strings_from_js(thead)?
+    };
+    Some((thead, rows_from_js(tbody)?))
+}
+
+// 配列の配列だけ受け付ける
+pub(crate) fn rows_from_js(value: &JsValue) -> Option<Vec<Vec<String>>> {
+    value
+        .dyn_ref::<js_sys::Array>()?
+        .iter()
+        .map(|row| strings_from_js(&row))
+        .collect()
+}
@@ -0,0 +1,102 @@
+use crate::binder::camera::PinchState;
+use crate::figure::hover::HoverTarget;
+use crate::math::Point;
+use std::collections::HashMap;
+
+// PointerEvent.pointerType に対応する
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum PointerType {
+    Mouse,
+    Pen,
+    Touch,
+}
+
+impl PointerType {
+    // 知らない値は Mouse とみなす
+    pub(crate) fn from_name(name: &str) -> PointerType {
+        match name {
+            "pen" => PointerType::Pen,
+            "touch" => PointerType::Touch,
+            _ => PointerType::Mouse,
+        }
+    }
+}
+
+// 押されている間の一つのポインターの状態
+pub(crate) struct PointerState {
+    pub(crate) pointer_type: PointerType,
+    pub(crate) pressure: f64,
+    pub(crate) is_primary: bool,
+    // つかんでいる Figure の id
+    pub(crate) figure_id: Option<usize>,
+    pub(crate) is_dragged: bool,
+    pub(crate) is_button_pushed: bool,
+    pub(crate) drag_start_point: Point,
+    // カメラを通す前の現在位置
+    pub(crate) view_point: Point,
+}
+
+impl PointerState {
+    pub(crate) fn new(pointer_type: PointerType, pressure: f64, is_primary: bool) -> PointerState {
+        PointerState {
+            pointer_type,
+            pressure,
+            is_primary,
+            figure_id: None,
+            is_dragged: false,
+            is_button_pushed: false,
+            drag_start_point: Point::new(),
+            view_point: Point::new(),
+        }
+    }
+    // タッチには hover がないので、マウスとペンの primary だけを hover の対象にする
+    pub(crate) fn can_hover(pointer_type: PointerType, is_primary: bool) -> bool {
+        pointer_type != PointerType::Touch && is_primary
+    }
+}
+
+pub(crate) struct PointerStates {
+    // pointerId ごとの状態。押されていないポインターは持たない
+    pub(crate) pointers: HashMap<i32, PointerState>,
+    // マウスポインターが乗っている Figure の id と場所
+    pub(crate) hover: Option<(usize, HoverTarget)>,
+    pub(crate) pinch: Option<PinchState>,
+}
+
+impl PointerStates {
+    pub(crate) fn new() -> PointerStates {
+        PointerStates {
+            pointers: HashMap::new(),
+            hover: None,
+            pinch: None,
+        }
+    }
+    // Figure をつかんでいないタッチで、pointer_id 以外のもの
+    pub(crate) fn free_touch_id(&self, pointer_id: i32) -> Option<i32> {
+        self.pointers
+            .iter()
+            .find(|(id, pointer)| {
+                **id != pointer_id
+                    && pointer.pointer_type == PointerType::Touch
+                    && pointer.figure_id.is_none()
+            })
+            .map(|(id, _)| *id)
+    }
+    // ピンチに使っていた指が離れたらピンチを終える
+    pub(crate) fn remove(&mut self, pointer_id: i32) -> Option<PointerState> {
+        if self
+            .pinch
+            .as_ref()
+            .is_some_and(|pinch| pinch.contains(pointer_id))
+        {
+            self.pinch = None;
+        }
+        self.pointers.remove(&pointer_id)
+    }
+    // 一つの Figure は一つのポインターでしかつかめない
+    pub(crate) fn is_grabbing(&self, figure_id: usize) -> bool {
+        self.pointers
+            .values()
+            .any(|pointer| pointer.figure_id == Some(figure_id))
+    }
+}
@@ -1,131 +1,864 @@
 use crate::binder::element_manager::ElementManager;
 use crate::binder::ContentManager;
+use crate::expression::{ParseError, Template};
 use crate::figure::TemporaryState;
-use web_sys::{Document, Element};
+use crate::render_backend::NodeId;
+use crate::table_style::RowStyle;
+use std::collections::{BTreeMap, VecDeque};
+use std::ops::Range;
+use wasm_bindgen::prelude::wasm_bindgen;
+
+// 左端と列の間の余白
+const COLUMN_PADDING: f64 = 5.0;
+const COLUMN_GAP: f64 = 10.0;
+// 縦のスクロールバーの太さと、PartRect::adjust_scrollable が内容の大きさに足す余白
+const SCROLL_BAR_WIDTH: f64 = 10.0;
+const CONTENT_MARGIN: f64 = 10.0;
+// 表示領域の幅からこの分を除いて列を置く。縦のスクロールバーと重ならず、横にもはみ出さない
+const RESERVED_WIDTH: f64 = SCROLL_BAR_WIDTH + CONTENT_MARGIN;
 
 #[derive(Clone, Debug)]
+#[allow(dead_code)]
 pub(crate) struct TableContentState {
     content_key: String,
+    // 見出しは 1 行。縦のスクロールでは動かさず、横には本体と一緒に動かす
     thead_data: Vec<StringBinder>,
-    thead_column_styles: Vec<ColumnStyle>,
+    pub(crate) thead_column_styles: Vec<ColumnStyle>,
+    // tbody_data[行][列]。値は content_manager から取得した値と、それが参照する state から作る
     pub(crate) tbody_data: Vec<Vec<StringBinder>>,
     pub(crate) tbody_column_styles: Vec<ColumnStyle>,
     pub(crate) content_id_token: String,
+    // content_manager から取得した値。TableContent::uses_templates が true の時は、式を評価して表示する
+    thead_values: Vec<String>,
+    tbody_values: Vec<Vec<String>>,
+    elements: Option<TableElements>,
+    rendered_rows: BTreeMap<usize, RenderedRow>,
+    // content_manager の TableStyle から求めた行ごとの装飾。TableStyle が無い時は空
+    row_styles: Vec<RowStyle>,
+    // 描画済みの行を置いた時の見出しの高さ
+    rendered_header_height: f64,
+    // 列ごとに測った内容の幅。スクロールで行が入れ替わっても縮めず、内容が変わった時に測り直す
+    measured_widths: Vec<f64>,
+    // 解析できなかった値。Binder::update で取り出して通知する
+    template_errors: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+struct TableElements {
+    group: NodeId,
+    clip_rect: NodeId,
+    // 行の背景の rect を入れる g。背景が必要になった時に作る
+    row_backgrounds: Option<NodeId>,
+    // 列ごとの text 要素
+    columns: Vec<NodeId>,
+    header: Option<HeaderElements>,
+}
+
+impl TableElements {
+    // 文字より奥に描くため、作った時に列の text と見出しを手前に移す
+    fn row_backgrounds(&mut self, element_manager: &mut ElementManager) -> NodeId {
+        if let Some(row_backgrounds) = self.row_backgrounds {
+            return row_backgrounds;
+        }
+        let row_backgrounds = element_manager.backend.create_element(self.group, "g");
+        for column in self.columns.iter() {
+            element_manager.backend.append_child(self.group, *column);
+        }
+        if let Some(header) = &self.header {
+            element_manager
+                .backend
+                .append_child(self.group, header.group);
+        }
+        self.row_backgrounds = Some(row_backgrounds);
+        row_backgrounds
+    }
+}
+
+#[derive(Clone, Debug)]
+struct RenderedRow {
+    // 列の番号順に並ぶ
+    tspans: Vec<NodeId>,
+    background: Option<NodeId>,
+    // tspans と background に反映済みの装飾
+    style: RowStyle,
+}
+
+#[derive(Clone, Debug)]
+struct HeaderElements {
+    group: NodeId,
+    // 下を流れる本体の行を隠す
+    background: NodeId,
+    tspans: Vec<NodeId>,
 }
 
 impl TableContentState {
-    pub(crate) fn new(token: &str) -> TableContentState {
+    pub(crate) fn new(token: &str, content_key: &str) -> TableContentState {
         TableContentState {
-            content_key: "my_new_content".to_string(),
+            content_key: content_key.to_string(),
             thead_data: vec![],
             thead_column_styles: vec![],
             tbody_data: vec![],
             tbody_column_styles: vec![],
             content_id_token: token.to_string(),
+            thead_values: vec![],
+            tbody_values: vec![],
+            elements: None,
+            rendered_rows: BTreeMap::new(),
+            row_styles: vec![],
+            rendered_header_height: 0.0,
+            measured_widths: vec![],
+            template_errors: vec![],
         }
     }
-    pub(crate) fn init(
-        &self,
-        element_manager: &ElementManager,
-        table_container: &Element,
+    // 見出しには本体の列に合わせたスタイルを使う
+    pub(crate) fn set_column_styles(&mut self, column_styles: Vec<ColumnStyle>) {
+        self.thead_column_styles = column_styles
+            .iter()
+            .map(ColumnStyle::header_style)
+            .collect();
+        self.tbody_column_styles = column_styles;
+    }
+    pub(crate) fn content_key(&self) -> &str {
+        self.content_key.as_str()
+    }
+    pub(crate) fn row_count(&self) -> usize {
+        self.tbody_data.len()
+    }
+    // 最新の値を取得して、値が変わったセルの tspan だけ書き換える
+    // 行や列の数が変わった時も含めて、何か変わったら true を返す
+    pub(crate) fn refresh(
+        &mut self,
+        element_manager: &mut ElementManager,
         content_manager: &ContentManager,
-    ) {
-        // TODO
-        // StringBinderから値を取り出す
-        // thead が空でない場合は thead の値を計算
-        if !self.thead_data.is_empty() {
-            // check_and_update
-        }
-        if let Some(content_tbody) = &content_manager.get_tbody(&self.content_key.as_str()) {
-            // tbody が空でない場合は tbody の値を計算
-            let mut tbody_column_elements = vec![];
-            for n in 0..content_tbody.len() {
-                // TODO
-                // adjust の中では mut にできない
-                // for m in 0..self.tbody_data[n].len() {
-                //   self.tbody_data[n][m].check_and_update_value(&State {});
-                // }
-                let tbody_column_id = format!("{}-tbody-col-{}", self.content_id_token, n);
-                if let Some(tbody_col) = element_manager
-                    .document
-                    .get_element_by_id(tbody_column_id.as_str())
+    ) -> bool {
+        self.thead_values = content_manager
+            .get_thead(self.content_key.as_str())
+            .unwrap_or_default();
+        self.tbody_values = content_manager
+            .get_tbody(self.content_key.as_str())
+            .unwrap_or_default();
+        let mut is_changed = false;
+        if self.thead_data.len() != self.thead_values.len() {
+            // 列の数が変わった見出しは描き直す
+            self.thead_data = (0..self.thead_values.len())
+                .map(StringBinder::new_with_header)
+                .collect();
+            self.remove_header(element_manager);
+            is_changed = true;
+        }
+        let state = TemporaryState {
+            store: &content_manager.state_store,
+            thead: &self.thead_values,
+            tbody: &self.tbody_values,
+            uses_templates: content_manager.uses_templates(self.content_key.as_str()),
+        };
+        let header_tspans = self
+            .elements
+            .as_ref()
+            .and_then(|elements| elements.header.as_ref())
+            .map(|header| header.tspans.as_slice())
+            .unwrap_or_default();
+        for (m, binder) in self.thead_data.iter_mut().enumerate() {
+            let is_updated = binder.check_and_update_value(&state);
+            if let Some(error) = binder.take_parse_error() {
+                self.template_errors.push(format!(
+                    "{} の見出しの {} 列目: {}",
+                    self.content_key, m, error
+                ));
+            }
+            if is_updated {
+                is_changed = true;
+                if let Some(tspan) = header_tspans.get(m) {
+                    element_manager
+                        .backend
+                        .set_text(*tspan, binder.current_value.as_str());
+                }
+            }
+        }
+        let row_count = self.tbody_values.len();
+        is_changed |= self.tbody_data.len() != row_count;
+        self.tbody_data.truncate(row_count);
+        let mut removed_rows: Vec<usize> = self
+            .rendered_rows
+            .range(row_count..)
+            .map(|(n, _)| *n)
+            .collect();
+        for (n, row) in self.tbody_values.iter().enumerate() {
+            if self.tbody_data.len() <= n {
+                self.tbody_data.push(vec![]);
+            }
+            let binders = &mut self.tbody_data[n];
+            if binders.len() != row.len() {
+                // 列の数が変わった行は描き直す
+                binders.truncate(row.len());
+                for m in binders.len()..row.len() {
+                    binders.push(StringBinder::new_with_cell(n, m));
+                }
+                removed_rows.push(n);
+                is_changed = true;
+            }
+            for (m, binder) in binders.iter_mut().enumerate() {
+                let is_updated = binder.check_and_update_value(&state);
+                if let Some(error) = binder.take_parse_error() {
+                    self.template_errors.push(format!(
+                        "{} の {} 行 {} 列目: {}",
+                        self.content_key, n, m, error
+                    ));
+                }
+                if !is_updated {
+                    continue;
+                }
+                is_changed = true;
+                if let Some(tspan) = self
+                    .rendered_rows
+                    .get(&n)
+                    .and_then(|rendered_row| rendered_row.tspans.get(m))
                 {
-                    tbody_col.remove();
+                    element_manager
+                        .backend
+                        .set_text(*tspan, binder.current_value.as_str());
                 }
-                // text要素を column の数だけ生成
-                let tbody_column = element_manager
-                    .document
-                    .create_element_ns(Option::from("http://www.w3.org/2000/svg"), "text")
-                    .unwrap();
-                tbody_column.set_id(tbody_column_id.as_str());
-                table_container.append_child(&*tbody_column).unwrap();
-                tbody_column_elements.push(tbody_column);
-            }
-            // 各text要素にtspanを追加
-            for n in 0..content_tbody.len() {
-                for m in 0..content_tbody[n].len() {
-                    let tbody_tspan = element_manager
-                        .document
-                        .create_element_ns(Option::from("http://www.w3.org/2000/svg"), "tspan")
-                        .unwrap();
-                    let value = &content_tbody[n][m];
-                    tbody_tspan.set_inner_html(value.as_str());
-                    let style = &self.tbody_column_styles[m];
-                    if style.first_y != 0.0 {
-                        tbody_tspan
-                            .set_attribute("y", style.first_y.to_string().as_str())
-                            .unwrap();
-                    }
-                    if let TextAnchorType::End = style.text_anchor_type {
-                        tbody_tspan.set_attribute("text-anchor", "end").unwrap();
-                    }
-                    tbody_tspan
-                        .set_attribute("x", style.x.to_string().as_str())
-                        .unwrap();
-                    tbody_tspan
-                        .set_attribute("dy", (style.dy * n as f64).to_string().as_str())
-                        .unwrap();
-                    tbody_tspan
-                        .set_attribute("font-size", style.font_size.to_string().as_str())
-                        .unwrap();
-                    tbody_column_elements[m]
-                        .append_child(&*tbody_tspan)
-                        .unwrap();
+            }
+        }
+        for n in removed_rows {
+            self.remove_row(element_manager, n);
+        }
+        self.row_styles = match content_manager.table_styles.get(self.content_key.as_str()) {
+            Some(table_style) => self
+                .tbody_data
+                .iter()
+                .enumerate()
+                .map(|(n, binders)| {
+                    let values: Vec<&str> = binders
+                        .iter()
+                        .map(|binder| binder.current_value.as_str())
+                        .collect();
+                    table_style.resolve(n, &values, &content_manager.state_store)
+                })
+                .collect(),
+            None => vec![],
+        };
+        let rendered_rows: Vec<usize> = self.rendered_rows.keys().copied().collect();
+        for n in rendered_rows {
+            is_changed |= self.apply_row_style(element_manager, n);
+        }
+        if is_changed {
+            self.measured_widths.clear();
+        }
+        is_changed
+    }
+    // 描画済みの n 行目に装飾を反映する。変わった時は true を返す
+    fn apply_row_style(&mut self, element_manager: &mut ElementManager, n: usize) -> bool {
+        let style = self.row_styles.get(n).cloned().unwrap_or_default();
+        let y = self.rendered_header_height + self.row_height() * n as f64;
+        let height = self.row_height();
+        let (Some(elements), Some(rendered_row)) =
+            (self.elements.as_mut(), self.rendered_rows.get_mut(&n))
+        else {
+            return false;
+        };
+        if rendered_row.style == style {
+            return false;
+        }
+        for (m, tspan) in rendered_row.tspans.iter().enumerate() {
+            style
+                .cells
+                .get(m)
+                .cloned()
+                .unwrap_or_default()
+                .apply(element_manager, *tspan);
+        }
+        match (&style.background, rendered_row.background) {
+            (Some(fill), Some(background)) => {
+                element_manager
+                    .backend
+                    .set_attribute(background, "fill", fill);
+            }
+            (Some(fill), None) => {
+                // x と width は内容の大きさを測った後で stretch_row_backgrounds が決める
+                let row_backgrounds = elements.row_backgrounds(element_manager);
+                let background = element_manager
+                    .backend
+                    .create_element(row_backgrounds, "rect");
+                for (name, value) in [
+                    ("fill", fill.clone()),
+                    ("y", y.to_string()),
+                    ("width", "0".to_string()),
+                    ("height", height.to_string()),
+                ] {
+                    element_manager
+                        .backend
+                        .set_attribute(background, name, value.as_str());
                 }
+                rendered_row.background = Some(background);
+            }
+            (None, Some(background)) => {
+                element_manager.backend.remove(background);
+                rendered_row.background = None;
+            }
+            (None, None) => {}
+        }
+        rendered_row.style = style;
+        true
+    }
+    pub(crate) fn take_template_errors(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.template_errors)
+    }
+    fn row_height(&self) -> f64 {
+        max_dy(&self.tbody_column_styles)
+    }
+    fn header_height(&self) -> f64 {
+        if self.thead_data.is_empty() {
+            0.0
+        } else {
+            max_dy(&self.thead_column_styles)
+        }
+    }
+    // 描画していない行も含めた高さ。スクロールバーの長さはこれで決める
+    pub(crate) fn logical_height(&self) -> f64 {
+        self.header_height() + self.row_height() * self.row_count() as f64
+    }
+    // 内容の offset px から viewport_height px の範囲に入る行。前後に 1 行ずつ余分に描く
+    // 見出しに隠れる分は除く
+    pub(crate) fn visible_rows(&self, offset: f64, viewport_height: f64) -> Range<usize> {
+        let row_height = self.row_height();
+        let row_count = self.row_count();
+        if row_height <= 0.0 {
+            return 0..row_count;
+        }
+        let body_height = (viewport_height - self.header_height()).max(0.0);
+        let start = (offset / row_height).floor().max(0.0) as usize;
+        let end = ((offset + body_height) / row_height).ceil() as usize;
+        start.saturating_sub(1).min(row_count)..(end + 1).min(row_count)
+    }
+    // 初回だけ clipPath を作る。rows に入る行のうち、まだ描いていない行の tspan を足し、
+    // 外れた行の tspan を消す。clipPath の rect を返す
+    pub(crate) fn render(
+        &mut self,
+        element_manager: &mut ElementManager,
+        content_group: NodeId,
+        rows: Range<usize>,
+    ) -> NodeId {
+        let mut elements = match self.elements.take() {
+            Some(elements) => elements,
+            None => {
+                let clip_path = element_manager
+                    .backend
+                    .create_element(content_group, "clipPath");
+                element_manager.backend.set_attribute(
+                    clip_path,
+                    "id",
+                    format!("clip-path-table-content-{}", self.content_id_token).as_str(),
+                );
+                TableElements {
+                    group: content_group,
+                    clip_rect: element_manager.backend.create_element(clip_path, "rect"),
+                    row_backgrounds: None,
+                    columns: vec![],
+                    header: None,
+                }
+            }
+        };
+        // 見出しの高さが変わったら本体の行を置き直す
+        let header_height = self.header_height();
+        if header_height != self.rendered_header_height {
+            let rendered_rows: Vec<usize> = self.rendered_rows.keys().copied().collect();
+            for n in rendered_rows {
+                self.remove_row(element_manager, n);
+            }
+            self.rendered_header_height = header_height;
+        }
+        // text要素を column の数だけ生成
+        let column_length = self
+            .tbody_data
+            .iter()
+            .map(|row| row.len())
+            .max()
+            .unwrap_or(0);
+        let mut is_header_behind =
+            elements.columns.len() < column_length.min(self.tbody_column_styles.len());
+        for n in elements.columns.len()..column_length.min(self.tbody_column_styles.len()) {
+            let tbody_column = element_manager
+                .backend
+                .create_element(content_group, "text");
+            element_manager.backend.set_attribute(
+                tbody_column,
+                "id",
+                format!("{}-tbody-col-{}", self.content_id_token, n).as_str(),
+            );
+            elements.columns.push(tbody_column);
+        }
+        if elements.header.is_none() && !self.thead_data.is_empty() {
+            elements.header = Some(self.create_header(element_manager, content_group));
+            is_header_behind = true;
+        }
+        if let Some(header) = &elements.header {
+            // 本体より手前に描く
+            if is_header_behind {
+                element_manager
+                    .backend
+                    .append_child(content_group, header.group);
+            }
+            // 背景は内容の大きさを測った後で pin_header が広げる
+            element_manager
+                .backend
+                .set_attribute(header.background, "width", "0");
+        }
+        // 行の背景も同じく幅を 0 にしてから測る
+        for background in self
+            .rendered_rows
+            .values()
+            .filter_map(|rendered_row| rendered_row.background)
+        {
+            element_manager
+                .backend
+                .set_attribute(background, "width", "0");
+        }
+        let outside_rows: Vec<usize> = self
+            .rendered_rows
+            .keys()
+            .filter(|n| !rows.contains(n))
+            .copied()
+            .collect();
+        for n in outside_rows {
+            self.remove_row(element_manager, n);
+        }
+        // 各text要素に行の tspan を追加。位置は行の番号から決まる
+        let mut added_rows = vec![];
+        for n in rows {
+            if self.rendered_rows.contains_key(&n) {
+                continue;
+            }
+            // スタイルが定義されていない列は表示しない
+            let tspans = self.tbody_data[n]
+                .iter()
+                .zip(self.tbody_column_styles.iter())
+                .zip(elements.columns.iter())
+                .map(|((binder, style), column)| {
+                    style.create_row_tspan(
+                        element_manager,
+                        *column,
+                        n,
+                        header_height,
+                        binder.current_value.as_str(),
+                    )
+                })
+                .collect();
+            self.rendered_rows.insert(
+                n,
+                RenderedRow {
+                    tspans,
+                    background: None,
+                    style: RowStyle::default(),
+                },
+            );
+            added_rows.push(n);
+        }
+        let clip_rect = elements.clip_rect;
+        self.elements = Some(elements);
+        for n in added_rows {
+            self.apply_row_style(element_manager, n);
+        }
+        clip_rect
+    }
+    fn create_header(
+        &self,
+        element_manager: &mut ElementManager,
+        content_group: NodeId,
+    ) -> HeaderElements {
+        let group = element_manager.backend.create_element(content_group, "g");
+        element_manager.backend.set_attribute(
+            group,
+            "id",
+            format!("{}-thead", self.content_id_token).as_str(),
+        );
+        let background = element_manager.backend.create_element(group, "rect");
+        element_manager
+            .backend
+            .set_attribute(background, "fill", "#eee");
+        element_manager.backend.set_attribute(
+            background,
+            "height",
+            self.header_height().to_string().as_str(),
+        );
+        let tspans = self
+            .thead_data
+            .iter()
+            .zip(self.thead_column_styles.iter())
+            .enumerate()
+            .map(|(n, (binder, style))| {
+                let thead_column = element_manager.backend.create_element(group, "text");
+                element_manager.backend.set_attribute(
+                    thead_column,
+                    "id",
+                    format!("{}-thead-col-{}", self.content_id_token, n).as_str(),
+                );
+                style.create_row_tspan(
+                    element_manager,
+                    thead_column,
+                    0,
+                    0.0,
+                    binder.current_value.as_str(),
+                )
+            })
+            .collect();
+        HeaderElements {
+            group,
+            background,
+            tspans,
+        }
+    }
+    // 見出しを表示領域の上端に留める。x, y は表示領域の左上の内容での位置
+    pub(crate) fn pin_header(
+        &self,
+        element_manager: &mut ElementManager,
+        x: f64,
+        y: f64,
+        width: f64,
+    ) {
+        let Some(header) = self
+            .elements
+            .as_ref()
+            .and_then(|elements| elements.header.as_ref())
+        else {
+            return;
+        };
+        element_manager.backend.set_attribute(
+            header.group,
+            "transform",
+            format!("translate(0, {})", y).as_str(),
+        );
+        element_manager
+            .backend
+            .set_attribute(header.background, "x", x.to_string().as_str());
+        element_manager.backend.set_attribute(
+            header.background,
+            "width",
+            width.to_string().as_str(),
+        );
+    }
+    // 描画した内容から列の幅を決めて、左から順に x を置き直す。width は表示領域の幅
+    pub(crate) fn layout_columns(&mut self, element_manager: &mut ElementManager, width: f64) {
+        let Some(elements) = self.elements.as_ref() else {
+            return;
+        };
+        let header_tspans = elements
+            .header
+            .as_ref()
+            .map(|header| header.tspans.as_slice())
+            .unwrap_or_default();
+        self.measured_widths
+            .resize(self.tbody_column_styles.len(), 0.0);
+        for (n, measured_width) in self.measured_widths.iter_mut().enumerate() {
+            for node in [
+                elements.columns.get(n).copied(),
+                header_tspans.get(n).copied(),
+            ]
+            .iter()
+            .flatten()
+            {
+                *measured_width =
+                    measured_width.max(element_manager.backend.bounding_size(*node).0);
+            }
+        }
+        let widths = column_widths(
+            &self.tbody_column_styles,
+            &self.measured_widths,
+            width - RESERVED_WIDTH,
+        );
+        let mut left = COLUMN_PADDING;
+        for (n, column_width) in widths.into_iter().enumerate() {
+            let style = &mut self.tbody_column_styles[n];
+            let x = match style.text_anchor_type {
+                TextAnchorType::Start => left,
+                TextAnchorType::Middle => left + column_width / 2.0,
+                TextAnchorType::End => left + column_width,
+            };
+            left += column_width + COLUMN_GAP;
+            if style.x == x {
+                continue;
+            }
+            style.x = x;
+            if let Some(header_style) = self.thead_column_styles.get_mut(n) {
+                header_style.x = x;
             }
+            let tspans = self
+                .rendered_rows
+                .values()
+                .filter_map(|rendered_row| rendered_row.tspans.get(n))
+                .chain(header_tspans.get(n));
+            for tspan in tspans {
+                element_manager
+                    .backend
+                    .set_attribute(*tspan, "x", x.to_string().as_str());
+            }
+        }
+    }
+    // 行の背景を表示領域の幅に合わせる。x は表示領域の左端の内容での位置
+    pub(crate) fn stretch_row_backgrounds(
+        &self,
+        element_manager: &mut ElementManager,
+        x: f64,
+        width: f64,
+    ) {
+        for background in self
+            .rendered_rows
+            .values()
+            .filter_map(|rendered_row| rendered_row.background)
+        {
+            element_manager
+                .backend
+                .set_attribute(background, "x", x.to_string().as_str());
+            element_manager
+                .backend
+                .set_attribute(background, "width", width.to_string().as_str());
+        }
+    }
+    fn remove_header(&mut self, element_manager: &mut ElementManager) {
+        if let Some(header) = self
+            .elements
+            .as_mut()
+            .and_then(|elements| elements.header.take())
+        {
+            element_manager.backend.remove(header.group);
+        }
+    }
+    fn remove_row(&mut self, element_manager: &mut ElementManager, row: usize) {
+        if let Some(rendered_row) = self.rendered_rows.remove(&row) {
+            for tspan in rendered_row.tspans {
+                element_manager.backend.remove(tspan);
+            }
+            if let Some(background) = rendered_row.background {
+                element_manager.backend.remove(background);
+            }
+        }
+    }
+}
+
+// push_line で行を足していくログ。max_lines を超えたら古い行から捨てる
+// 描画は前回からの差分だけで、追加された行の tspan を足し、捨てた行の tspan を消す
+#[derive(Clone, Debug)]
+pub(crate) struct LogContentState {
+    lines: VecDeque<String>,
+    max_lines: usize,
+    // これまでに追加した行の総数。n 行目の tspan は dy = n * 行の高さ に置く
+    total_count: usize,
+    column_style: ColumnStyle,
+    pub(crate) content_id_token: String,
+    elements: Option<LogElements>,
+    // 描画済みの行の tspan。最後の要素が rendered_count - 1 行目
+    tspans: VecDeque<NodeId>,
+    rendered_count: usize,
+    // line_group を上にずらしている行数
+    first_row: usize,
+}
+
+#[derive(Clone, Debug)]
+struct LogElements {
+    clip_rect: NodeId,
+    // 捨てた行の分だけ上にずらす
+    line_group: NodeId,
+    text: NodeId,
+}
+
+impl LogContentState {
+    pub(crate) fn new(token: &str, max_lines: usize) -> LogContentState {
+        LogContentState {
+            lines: VecDeque::new(),
+            max_lines,
+            total_count: 0,
+            column_style: ColumnStyle::log_columns().remove(0),
+            content_id_token: token.to_string(),
+            elements: None,
+            tspans: VecDeque::new(),
+            rendered_count: 0,
+            first_row: 0,
+        }
+    }
+    pub(crate) fn push_line(&mut self, line: &str) {
+        self.lines.push_back(line.to_string());
+        self.total_count += 1;
+        while self.lines.len() > self.max_lines {
+            self.lines.pop_front();
         }
     }
-    fn update(&self) {}
+    pub(crate) fn total_count(&self) -> usize {
+        self.total_count
+    }
+    pub(crate) fn line_height(&self) -> f64 {
+        self.column_style.dy
+    }
+    // 初回だけ clipPath と text を作る。clipPath の rect と、捨てた行数を返す
+    pub(crate) fn render(
+        &mut self,
+        element_manager: &mut ElementManager,
+        content_group: NodeId,
+    ) -> (NodeId, usize) {
+        let elements = match &self.elements {
+            Some(elements) => elements.clone(),
+            None => {
+                let clip_path = element_manager
+                    .backend
+                    .create_element(content_group, "clipPath");
+                element_manager.backend.set_attribute(
+                    clip_path,
+                    "id",
+                    format!("clip-path-table-content-{}", self.content_id_token).as_str(),
+                );
+                let clip_rect = element_manager.backend.create_element(clip_path, "rect");
+                let line_group = element_manager.backend.create_element(content_group, "g");
+                let text = element_manager.backend.create_element(line_group, "text");
+                element_manager.backend.set_attribute(
+                    text,
+                    "id",
+                    format!("{}-log", self.content_id_token).as_str(),
+                );
+                let elements = LogElements {
+                    clip_rect,
+                    line_group,
+                    text,
+                };
+                self.elements = Some(elements.clone());
+                elements
+            }
+        };
+        let first_row = self.total_count - self.lines.len();
+        // 捨てた行の tspan を消す
+        while self.rendered_count - self.tspans.len() < first_row {
+            match self.tspans.pop_front() {
+                Some(tspan) => element_manager.backend.remove(tspan),
+                None => break,
+            }
+        }
+        // 追加された行の tspan を足す
+        for row in self.rendered_count.max(first_row)..self.total_count {
+            let tspan = self.column_style.create_row_tspan(
+                element_manager,
+                elements.text,
+                row,
+                0.0,
+                self.lines[row - first_row].as_str(),
+            );
+            self.tspans.push_back(tspan);
+        }
+        self.rendered_count = self.total_count;
+        let removed_count = first_row - self.first_row;
+        if removed_count > 0 {
+            self.first_row = first_row;
+            element_manager.backend.set_attribute(
+                elements.line_group,
+                "transform",
+                format!("translate(0, {})", -self.line_height() * first_row as f64).as_str(),
+            );
+        }
+        (elements.clip_rect, removed_count)
+    }
 }
 
 #[derive(Clone, Debug)]
 pub(crate) struct StringBinder {
-    value_func: fn(&StringBinder, &TemporaryState) -> String,
-    args_string: Vec<String>,
+    source: BinderSource,
     current_value: String,
+    // 直近の解析で見つかった誤り。呼び出し元が取り出して通知する
+    parse_error: Option<ParseError>,
 }
 
+#[derive(Clone, Debug)]
+enum BinderSource {
+    Text(String),
+    Template(Template),
+    // state の tbody と thead の値を Template として表示する。値が変わった時だけ解析し直す
+    Cell {
+        row: usize,
+        column: usize,
+        parsed: Option<ParsedValue>,
+    },
+    Header {
+        column: usize,
+        parsed: Option<ParsedValue>,
+    },
+}
+
+#[derive(Clone, Debug)]
+struct ParsedValue {
+    source: String,
+    // 解析できなかった時は source をそのまま表示する
+    template: Option<Template>,
+}
+
+#[allow(dead_code)]
 impl StringBinder {
-    fn args_string_func() -> fn(&StringBinder, &TemporaryState) -> String {
-        fn get_args_string(string_binder: &StringBinder, _: &TemporaryState) -> String {
-            if let Some(arg) = string_binder.args_string.get(0) {
-                arg.to_owned()
-            } else {
-                "".to_string()
-            }
+    fn new(source: BinderSource) -> StringBinder {
+        StringBinder {
+            source,
+            current_value: "".to_string(),
+            parse_error: None,
         }
-        get_args_string
     }
     pub(crate) fn new_with_str(arg: &str) -> StringBinder {
-        StringBinder {
-            value_func: StringBinder::args_string_func(),
-            args_string: vec![arg.to_string()],
-            current_value: arg.to_string(),
-        }
+        let mut string_binder = StringBinder::new(BinderSource::Text(arg.to_string()));
+        string_binder.current_value = arg.to_string();
+        string_binder
     }
-    fn get_value(&self, state: &TemporaryState) -> String {
-        let value_func = self.value_func;
-        value_func(self, state)
+    // "{hp}/{mhp}" のように state の値を使う式を埋め込んだ文字列
+    pub(crate) fn new_with_template(template: &str) -> Result<StringBinder, ParseError> {
+        Ok(StringBinder::new(BinderSource::Template(Template::parse(
+            template,
+        )?)))
+    }
+    // state の tbody の row 行 column 列の値。最初の check_and_update_value で値が入る
+    pub(crate) fn new_with_cell(row: usize, column: usize) -> StringBinder {
+        StringBinder::new(BinderSource::Cell {
+            row,
+            column,
+            parsed: None,
+        })
+    }
+    // state の thead の column 列の値
+    pub(crate) fn new_with_header(column: usize) -> StringBinder {
+        StringBinder::new(BinderSource::Header {
+            column,
+            parsed: None,
+        })
+    }
+    fn get_value(&mut self, state: &TemporaryState) -> String {
+        let (source, parsed) = match &mut self.source {
+            BinderSource::Text(text) => return text.clone(),
+            BinderSource::Template(template) => return template.render(state.store),
+            BinderSource::Cell {
+                row,
+                column,
+                parsed,
+            } => (state.cell(*row, *column).unwrap_or(""), parsed),
+            BinderSource::Header { column, parsed } => (
+                state
+                    .thead
+                    .get(*column)
+                    .map(|value| value.as_str())
+                    .unwrap_or(""),
+                parsed,
+            ),
+        };
+        if !state.uses_templates {
+            return source.to_string();
+        }
+        if parsed.as_ref().map(|parsed| parsed.source.as_str()) != Some(source) {
+            let template = match Template::parse(source) {
+                Ok(template) => Some(template),
+                Err(error) => {
+                    self.parse_error = Some(error);
+                    None
+                }
+            };
+            *parsed = Some(ParsedValue {
+                source: source.to_string(),
+                template,
+            });
+        }
+        match parsed.as_ref().and_then(|parsed| parsed.template.as_ref()) {
+            Some(template) => template.render(state.store),
+            None => source.to_string(),
+        }
     }
 
     fn check_and_update_value(&mut self, state: &TemporaryState) -> bool {
@@ -137,6 +870,9 @@ impl StringBinder {
             false
         }
     }
+    fn take_parse_error(&mut self) -> Option<ParseError> {
+        self.parse_error.take()
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -145,6 +881,7 @@ pub(crate) struct ColumnStyle {
     // 使わない時はブランクでOK
     pub(crate) defs_id: String,
     pub(crate) text_anchor_type: TextAnchorType,
+    pub(crate) width: ColumnWidth,
     pub(crate) x: f64,
     pub(crate) font_size: f64,
     pub(crate) first_y: f64,
@@ -152,23 +889,145 @@ pub(crate) struct ColumnStyle {
 }
 
 impl ColumnStyle {
-    fn get_element_by_defs_id(&self, document: &Document) -> Element {
-        // TODO
-        // この実装はうまく動きません
-        // なぜならclone()では要素が移動してしまうから
-        let element = document
-            .get_element_by_id(self.defs_id.as_str())
-            .unwrap()
-            .clone();
-        element.remove_attribute("id").unwrap();
-        element
+    // x は layout_columns が決める。それまでは左端に置く
+    pub(crate) fn new(text_anchor_type: TextAnchorType, width: ColumnWidth) -> ColumnStyle {
+        ColumnStyle {
+            defs_id: "".to_string(),
+            text_anchor_type,
+            width,
+            x: COLUMN_PADDING,
+            font_size: 20.0,
+            first_y: 25.0,
+            dy: 25.0,
+        }
+    }
+    // 左寄せの 1 列
+    pub(crate) fn log_columns() -> Vec<ColumnStyle> {
+        vec![ColumnStyle::new(TextAnchorType::Start, ColumnWidth::Auto)]
+    }
+    // 左寄せの見出しと、残りの幅の右端に寄せた値の 2 列
+    pub(crate) fn default_columns() -> Vec<ColumnStyle> {
+        vec![
+            ColumnStyle::new(TextAnchorType::Start, ColumnWidth::Auto),
+            ColumnStyle::new(TextAnchorType::End, ColumnWidth::Fraction(1.0)),
+        ]
+    }
+    // 見出しの行。本体の列と同じ位置に小さめの文字で置く
+    fn header_style(&self) -> ColumnStyle {
+        ColumnStyle {
+            defs_id: "".to_string(),
+            text_anchor_type: self.text_anchor_type,
+            width: self.width,
+            x: self.x,
+            font_size: 16.0,
+            first_y: 19.0,
+            dy: 25.0,
+        }
+    }
+    // row 行目の値を表示する tspan を作る。offset_y だけ下にずらす
+    fn create_row_tspan(
+        &self,
+        element_manager: &mut ElementManager,
+        parent: NodeId,
+        row: usize,
+        offset_y: f64,
+        value: &str,
+    ) -> NodeId {
+        let tspan = self.create_tspan(element_manager, parent);
+        element_manager.backend.set_text(tspan, value);
+        let y = self.first_y + offset_y;
+        if y != 0.0 {
+            element_manager
+                .backend
+                .set_attribute(tspan, "y", y.to_string().as_str());
+        }
+        match self.text_anchor_type {
+            TextAnchorType::Start => {}
+            TextAnchorType::Middle => {
+                element_manager
+                    .backend
+                    .set_attribute(tspan, "text-anchor", "middle")
+            }
+            TextAnchorType::End => {
+                element_manager
+                    .backend
+                    .set_attribute(tspan, "text-anchor", "end")
+            }
+        }
+        element_manager
+            .backend
+            .set_attribute(tspan, "x", self.x.to_string().as_str());
+        element_manager.backend.set_attribute(
+            tspan,
+            "dy",
+            (self.dy * row as f64).to_string().as_str(),
+        );
+        element_manager.backend.set_attribute(
+            tspan,
+            "font-size",
+            self.font_size.to_string().as_str(),
+        );
+        tspan
+    }
+    fn create_tspan(&self, element_manager: &mut ElementManager, parent: NodeId) -> NodeId {
+        if self.defs_id.is_empty() {
+            element_manager.backend.create_element(parent, "tspan")
+        } else {
+            element_manager
+                .backend
+                .clone_template(parent, self.defs_id.as_str())
+        }
     }
 }
 
-#[derive(Clone, Debug)]
-pub(crate) enum TextAnchorType {
+// 列の幅を左から順に決める。Auto と Fraction の列は measured_widths より狭くしない
+fn column_widths(column_styles: &[ColumnStyle], measured_widths: &[f64], width: f64) -> Vec<f64> {
+    let gaps = COLUMN_PADDING + COLUMN_GAP * column_styles.len().saturating_sub(1) as f64;
+    let mut fixed_width = 0.0;
+    let mut fraction_total = 0.0;
+    for (style, measured_width) in column_styles.iter().zip(measured_widths.iter()) {
+        match style.width {
+            ColumnWidth::Auto => fixed_width += measured_width,
+            ColumnWidth::Fixed(width) => fixed_width += width,
+            ColumnWidth::Fraction(fraction) => fraction_total += fraction.max(0.0),
+        }
+    }
+    let remaining_width = (width - gaps - fixed_width).max(0.0);
+    column_styles
+        .iter()
+        .zip(measured_widths.iter())
+        .map(|(style, measured_width)| match style.width {
+            ColumnWidth::Auto => *measured_width,
+            ColumnWidth::Fixed(width) => width,
+            ColumnWidth::Fraction(fraction) if fraction_total > 0.0 => {
+                (remaining_width * fraction.max(0.0) / fraction_total).max(*measured_width)
+            }
+            ColumnWidth::Fraction(_) => *measured_width,
+        })
+        .collect()
+}
+
+fn max_dy(column_styles: &[ColumnStyle]) -> f64 {
+    column_styles
+        .iter()
+        .fold(0.0, |height, style| style.dy.max(height))
+}
+
+// 列の中で文字を寄せる位置
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextAnchorType {
     Start,
+    Middle,
     End,
-    // 用途があれば…
-    // Middle,
+}
+
+// 列の幅の決め方
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ColumnWidth {
+    // 描画した内容の幅
+    Auto,
+    Fixed(f64),
+    // 他の列を置いた残りの幅を比で分ける
+    Fraction(f64),
 }
@@ -0,0 +1,669 @@
+use crate::state_store::{StateStore, StateValue};
+use std::fmt;
+
+// 解析に失敗した位置（1 始まりの文字数）と理由
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ParseError {
+    pub(crate) position: usize,
+    pub(crate) message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} 文字目: {}", self.position, self.message)
+    }
+}
+
+// "{hp}/{mhp}" のような文字列を解析したもの
+// {} の中には式を書ける。{ と } そのものは {{ と }} で書く
+#[derive(Clone, Debug)]
+pub(crate) struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Clone, Debug)]
+enum TemplatePart {
+    Text(String),
+    Expression(Expression),
+}
+
+impl Template {
+    pub(crate) fn parse(source: &str) -> Result<Template, ParseError> {
+        let mut parser = Parser::new(source);
+        let mut parts = vec![];
+        let mut text = String::new();
+        while let Some(c) = parser.next_char() {
+            match c {
+                '{' if parser.peek_char() == Some('{') => {
+                    parser.next_char();
+                    text.push('{');
+                }
+                '}' if parser.peek_char() == Some('}') => {
+                    parser.next_char();
+                    text.push('}');
+                }
+                '{' => {
+                    let start = parser.index;
+                    if !text.is_empty() {
+                        parts.push(TemplatePart::Text(std::mem::take(&mut text)));
+                    }
+                    let expression = parser.parse_expression()?;
+                    parser.skip_whitespace();
+                    match parser.next_char() {
+                        Some('}') => parts.push(TemplatePart::Expression(expression)),
+                        Some(c) => {
+                            parser.index -= 1;
+                            return Err(parser.error(format!("'{}' の前に '}}' がありません", c)));
+                        }
+                        None => {
+                            return Err(ParseError {
+                                position: start,
+                                message: "'{' が閉じていません".to_string(),
+                            })
+                        }
+                    }
+                }
+                '}' => {
+                    parser.index -= 1;
+                    return Err(parser.error("'}' を表示する時は '}}' と書きます".to_string()));
+                }
+                _ => text.push(c),
+            }
+        }
+        if !text.is_empty() {
+            parts.push(TemplatePart::Text(text));
+        }
+        Ok(Template { parts })
+    }
+    pub(crate) fn render(&self, store: &StateStore) -> String {
+        let scope = Scope { store, value: None };
+        self.parts
+            .iter()
+            .map(|part| match part {
+                TemplatePart::Text(text) => text.clone(),
+                TemplatePart::Expression(expression) => expression.evaluate(&scope).to_string(),
+            })
+            .collect()
+    }
+}
+
+// StyleRule の when。{} で囲まずに式だけを書く
+#[derive(Clone, Debug)]
+pub(crate) struct Condition {
+    expression: Expression,
+}
+
+impl Condition {
+    pub(crate) fn parse(source: &str) -> Result<Condition, ParseError> {
+        let mut parser = Parser::new(source);
+        let expression = parser.parse_expression()?;
+        parser.skip_whitespace();
+        if let Some(c) = parser.peek_char() {
+            return Err(parser.error(format!("'{}' は使えません", c)));
+        }
+        Ok(Condition { expression })
+    }
+    // value はセルに表示している値。式の中では value で参照し、数値として読めれば数値として扱う
+    pub(crate) fn is_met(&self, store: &StateStore, value: Option<&str>) -> bool {
+        let value = value.map(|value| match value.trim().parse::<f64>() {
+            Ok(number) if number.is_finite() => Value::Number(number),
+            _ => Value::Text(value.to_string()),
+        });
+        self.expression
+            .evaluate(&Scope { store, value })
+            .is_truthy()
+    }
+}
+
+// 式を評価する時に参照する値
+struct Scope<'a> {
+    store: &'a StateStore,
+    // Condition で、条件を付けたセルの値
+    value: Option<Value>,
+}
+
+#[derive(Clone, Debug)]
+enum Expression {
+    Literal(Value),
+    // "player1.hp" のような state の path
+    Path(String),
+    Unary(UnaryOperator, Box<Expression>),
+    Binary(BinaryOperator, Box<Expression>, Box<Expression>),
+    // 条件 ? 真の時 : 偽の時
+    Conditional(Box<Expression>, Box<Expression>, Box<Expression>),
+    Call(Function, Vec<Expression>),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum UnaryOperator {
+    Negate,
+    Not,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    Or,
+}
+
+// pad と pad_end で埋める幅の上限
+const MAX_PAD_WIDTH: f64 = 1024.0;
+
+#[derive(Clone, Copy, Debug)]
+enum Function {
+    // fixed(数値, 小数の桁数)
+    Fixed,
+    // sep(数値) で 3 桁ごとに , を入れる
+    Separate,
+    // pad(値, 幅, 埋める文字) で左を埋める。埋める文字を省略すると空白
+    Pad,
+    PadEnd,
+    // plural(数, 単数形, 複数形)
+    Plural,
+    // len(配列か文字列)
+    Length,
+}
+
+impl Function {
+    fn find(name: &str) -> Option<Function> {
+        match name {
+            "fixed" => Some(Function::Fixed),
+            "sep" => Some(Function::Separate),
+            "pad" => Some(Function::Pad),
+            "pad_end" => Some(Function::PadEnd),
+            "plural" => Some(Function::Plural),
+            "len" => Some(Function::Length),
+            _ => None,
+        }
+    }
+    // 受け付ける引数の数の最小と最大
+    fn arity(&self) -> (usize, usize) {
+        match self {
+            Function::Fixed => (1, 2),
+            Function::Separate | Function::Length => (1, 1),
+            Function::Pad | Function::PadEnd => (2, 3),
+            Function::Plural => (3, 3),
+        }
+    }
+    fn call(&self, args: &[Value]) -> Value {
+        match self {
+            Function::Fixed => {
+                let digits = args.get(1).map_or(0.0, Value::number_or_zero);
+                match args[0] {
+                    Value::Number(number) => {
+                        Value::Text(format!("{:.*}", digits.clamp(0.0, 20.0) as usize, number))
+                    }
+                    _ => Value::Null,
+                }
+            }
+            Function::Separate => Value::Text(separate(args[0].to_string().as_str())),
+            Function::Pad | Function::PadEnd => {
+                let text = args[0].to_string();
+                let width = args[1].number_or_zero().clamp(0.0, MAX_PAD_WIDTH) as usize;
+                let fill = args
+                    .get(2)
+                    .and_then(|fill| fill.to_string().chars().next())
+                    .unwrap_or(' ');
+                let padding: String = std::iter::repeat(fill)
+                    .take(width.saturating_sub(text.chars().count()))
+                    .collect();
+                match self {
+                    Function::Pad => Value::Text(padding + text.as_str()),
+                    _ => Value::Text(text + padding.as_str()),
+                }
+            }
+            Function::Plural => {
+                if args[0] == Value::Number(1.0) {
+                    args[1].clone()
+                } else {
+                    args[2].clone()
+                }
+            }
+            Function::Length => match &args[0] {
+                Value::List(items) => Value::Number(items.len() as f64),
+                Value::Text(text) => Value::Number(text.chars().count() as f64),
+                _ => Value::Null,
+            },
+        }
+    }
+}
+
+// 整数部に 3 桁ごとに , を入れる。数値でなければそのまま返す
+fn separate(text: &str) -> String {
+    let (sign, rest) = match text.strip_prefix('-') {
+        Some(rest) => ("-", rest),
+        None => ("", text),
+    };
+    let (integer, fraction) = rest.split_at(rest.find('.').unwrap_or(rest.len()));
+    if integer.is_empty() || !integer.chars().all(|c| c.is_ascii_digit()) {
+        return text.to_string();
+    }
+    let mut result = sign.to_string();
+    for (n, c) in integer.chars().enumerate() {
+        if n > 0 && (integer.len() - n) % 3 == 0 {
+            result.push(',');
+        }
+        result.push(c);
+    }
+    result + fraction
+}
+
+// 式の値。型が合わない演算の結果は Null にして、表示は空文字にする
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    Text(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    fn from_state(value: &StateValue) -> Value {
+        match value {
+            StateValue::Number(number) => Value::Number(*number),
+            StateValue::Bool(value) => Value::Bool(*value),
+            StateValue::Text(text) => Value::Text(text.clone()),
+            StateValue::List(items) => Value::List(items.iter().map(Value::from_state).collect()),
+        }
+    }
+    fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Bool(value) => *value,
+            Value::Number(number) => *number != 0.0 && !number.is_nan(),
+            Value::Text(text) => !text.is_empty(),
+            Value::List(items) => !items.is_empty(),
+        }
+    }
+    fn number_or_zero(&self) -> f64 {
+        match self {
+            Value::Number(number) => *number,
+            _ => 0.0,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => Ok(()),
+            Value::Bool(value) => write!(f, "{}", value),
+            Value::Number(number) => write!(f, "{}", number),
+            Value::Text(text) => write!(f, "{}", text),
+            Value::List(items) => {
+                for (n, item) in items.iter().enumerate() {
+                    if n > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Expression {
+    fn evaluate(&self, scope: &Scope) -> Value {
+        match self {
+            Expression::Literal(value) => value.clone(),
+            Expression::Path(path) => match (&scope.value, path.as_str()) {
+                (Some(value), "value") => value.clone(),
+                _ => scope
+                    .store
+                    .get(path.as_str())
+                    .map_or(Value::Null, Value::from_state),
+            },
+            Expression::Unary(operator, operand) => {
+                let operand = operand.evaluate(scope);
+                match (operator, operand) {
+                    (UnaryOperator::Negate, Value::Number(number)) => Value::Number(-number),
+                    (UnaryOperator::Negate, _) => Value::Null,
+                    (UnaryOperator::Not, operand) => Value::Bool(!operand.is_truthy()),
+                }
+            }
+            Expression::Binary(BinaryOperator::And, left, right) => {
+                Value::Bool(left.evaluate(scope).is_truthy() && right.evaluate(scope).is_truthy())
+            }
+            Expression::Binary(BinaryOperator::Or, left, right) => {
+                Value::Bool(left.evaluate(scope).is_truthy() || right.evaluate(scope).is_truthy())
+            }
+            Expression::Binary(operator, left, right) => {
+                binary(*operator, left.evaluate(scope), right.evaluate(scope))
+            }
+            Expression::Conditional(condition, when_true, when_false) => {
+                if condition.evaluate(scope).is_truthy() {
+                    when_true.evaluate(scope)
+                } else {
+                    when_false.evaluate(scope)
+                }
+            }
+            Expression::Call(function, args) => {
+                let args: Vec<Value> = args.iter().map(|arg| arg.evaluate(scope)).collect();
+                function.call(&args)
+            }
+        }
+    }
+}
+
+fn binary(operator: BinaryOperator, left: Value, right: Value) -> Value {
+    use BinaryOperator::*;
+    match (operator, &left, &right) {
+        (Add, Value::Number(left), Value::Number(right)) => Value::Number(left + right),
+        // どちらかが文字列ならつなげる
+        (Add, Value::Text(_), _) | (Add, _, Value::Text(_)) => {
+            Value::Text(format!("{}{}", left, right))
+        }
+        (Subtract, Value::Number(left), Value::Number(right)) => Value::Number(left - right),
+        (Multiply, Value::Number(left), Value::Number(right)) => Value::Number(left * right),
+        (Divide | Remainder, Value::Number(_), Value::Number(right)) if *right == 0.0 => {
+            Value::Null
+        }
+        (Divide, Value::Number(left), Value::Number(right)) => Value::Number(left / right),
+        (Remainder, Value::Number(left), Value::Number(right)) => Value::Number(left % right),
+        (Equal, _, _) => Value::Bool(left == right),
+        (NotEqual, _, _) => Value::Bool(left != right),
+        (Less | LessEqual | Greater | GreaterEqual, _, _) => {
+            let ordering = match (&left, &right) {
+                (Value::Number(left), Value::Number(right)) => left.partial_cmp(right),
+                (Value::Text(left), Value::Text(right)) => Some(left.cmp(right)),
+                _ => None,
+            };
+            Value::Bool(match ordering {
+                Some(ordering) => match operator {
+                    Less => ordering.is_lt(),
+                    LessEqual => ordering.is_le(),
+                    Greater => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                },
+                None => false,
+            })
+        }
+        _ => Value::Null,
+    }
+}
+
+// 入れ子の深さの上限。深い式で評価や解析のスタックが溢れないようにする
+const MAX_DEPTH: usize = 64;
+
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn new(source: &str) -> Parser {
+        Parser {
+            chars: source.chars().collect(),
+            index: 0,
+            depth: 0,
+        }
+    }
+    fn error(&self, message: String) -> ParseError {
+        ParseError {
+            position: self.index + 1,
+            message,
+        }
+    }
+    fn peek_char(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek_char()?;
+        self.index += 1;
+        Some(c)
+    }
+    fn skip_whitespace(&mut self) {
+        while self.peek_char().is_some_and(char::is_whitespace) {
+            self.index += 1;
+        }
+    }
+    // 空白の後に token が続いていれば読み進める
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let is_matched = token
+            .chars()
+            .enumerate()
+            .all(|(n, c)| self.chars.get(self.index + n) == Some(&c));
+        if is_matched {
+            self.index += token.chars().count();
+        }
+        is_matched
+    }
+    fn expect(&mut self, token: &str) -> Result<(), ParseError> {
+        if self.eat(token) {
+            Ok(())
+        } else {
+            Err(self.error(format!("'{}' がありません", token)))
+        }
+    }
+    // 入れ子を 1 段深くする。解析に失敗した時は戻さない
+    fn enter(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error("式の入れ子が深すぎます".to_string()));
+        }
+        Ok(())
+    }
+    fn parse_expression(&mut self) -> Result<Expression, ParseError> {
+        self.enter()?;
+        let expression = self.parse_conditional()?;
+        self.depth -= 1;
+        Ok(expression)
+    }
+    fn parse_conditional(&mut self) -> Result<Expression, ParseError> {
+        let condition = self.parse_or()?;
+        if !self.eat("?") {
+            return Ok(condition);
+        }
+        let when_true = self.parse_expression()?;
+        self.expect(":")?;
+        let when_false = self.parse_expression()?;
+        Ok(Expression::Conditional(
+            Box::new(condition),
+            Box::new(when_true),
+            Box::new(when_false),
+        ))
+    }
+    // 優先順位の低い演算子から順に、左結合で読む。演算子が続くほど木は深くなる
+    fn parse_binary(
+        &mut self,
+        operators: &[(&str, BinaryOperator)],
+        parse_operand: fn(&mut Parser) -> Result<Expression, ParseError>,
+    ) -> Result<Expression, ParseError> {
+        let depth = self.depth;
+        let mut left = parse_operand(self)?;
+        'operand: loop {
+            for (token, operator) in operators {
+                if self.eat(token) {
+                    self.enter()?;
+                    let right = parse_operand(self)?;
+                    left = Expression::Binary(*operator, Box::new(left), Box::new(right));
+                    continue 'operand;
+                }
+            }
+            self.depth = depth;
+            return Ok(left);
+        }
+    }
+    fn parse_or(&mut self) -> Result<Expression, ParseError> {
+        self.parse_binary(&[("||", BinaryOperator::Or)], Parser::parse_and)
+    }
+    fn parse_and(&mut self) -> Result<Expression, ParseError> {
+        self.parse_binary(&[("&&", BinaryOperator::And)], Parser::parse_equality)
+    }
+    fn parse_equality(&mut self) -> Result<Expression, ParseError> {
+        self.parse_binary(
+            &[
+                ("==", BinaryOperator::Equal),
+                ("!=", BinaryOperator::NotEqual),
+            ],
+            Parser::parse_comparison,
+        )
+    }
+    fn parse_comparison(&mut self) -> Result<Expression, ParseError> {
+        // <= を < より先に試す
+        self.parse_binary(
+            &[
+                ("<=", BinaryOperator::LessEqual),
+                (">=", BinaryOperator::GreaterEqual),
+                ("<", BinaryOperator::Less),
+                (">", BinaryOperator::Greater),
+            ],
+            Parser::parse_additive,
+        )
+    }
+    fn parse_additive(&mut self) -> Result<Expression, ParseError> {
+        self.parse_binary(
+            &[("+", BinaryOperator::Add), ("-", BinaryOperator::Subtract)],
+            Parser::parse_multiplicative,
+        )
+    }
+    fn parse_multiplicative(&mut self) -> Result<Expression, ParseError> {
+        self.parse_binary(
+            &[
+                ("*", BinaryOperator::Multiply),
+                ("/", BinaryOperator::Divide),
+                ("%", BinaryOperator::Remainder),
+            ],
+            Parser::parse_unary,
+        )
+    }
+    fn parse_unary(&mut self) -> Result<Expression, ParseError> {
+        if self.eat("-") {
+            self.enter()?;
+            let operand = self.parse_unary()?;
+            self.depth -= 1;
+            return Ok(Expression::Unary(UnaryOperator::Negate, Box::new(operand)));
+        }
+        self.skip_whitespace();
+        if self.peek_char() == Some('!') && self.chars.get(self.index + 1) != Some(&'=') {
+            self.index += 1;
+            self.enter()?;
+            let operand = self.parse_unary()?;
+            self.depth -= 1;
+            return Ok(Expression::Unary(UnaryOperator::Not, Box::new(operand)));
+        }
+        self.parse_primary()
+    }
+    fn parse_primary(&mut self) -> Result<Expression, ParseError> {
+        self.skip_whitespace();
+        match self.peek_char() {
+            Some('(') => {
+                self.index += 1;
+                let expression = self.parse_expression()?;
+                self.expect(")")?;
+                Ok(expression)
+            }
+            Some(c) if c.is_ascii_digit() => self.parse_number(),
+            Some(quote @ ('"' | '\'')) => self.parse_string(quote),
+            Some(c) if c.is_alphabetic() || c == '_' => self.parse_name(),
+            Some('}') | None => Err(self.error("式がありません".to_string())),
+            Some(c) => Err(self.error(format!("'{}' は使えません", c))),
+        }
+    }
+    fn parse_number(&mut self) -> Result<Expression, ParseError> {
+        let start = self.index;
+        while self.peek_char().is_some_and(|c| c.is_ascii_digit()) {
+            self.index += 1;
+        }
+        if self.peek_char() == Some('.')
+            && self
+                .chars
+                .get(self.index + 1)
+                .is_some_and(|c| c.is_ascii_digit())
+        {
+            self.index += 1;
+            while self.peek_char().is_some_and(|c| c.is_ascii_digit()) {
+                self.index += 1;
+            }
+        }
+        let text: String = self.chars[start..self.index].iter().collect();
+        Ok(Expression::Literal(Value::Number(text.parse().unwrap())))
+    }
+    fn parse_string(&mut self, quote: char) -> Result<Expression, ParseError> {
+        let start = self.index;
+        self.index += 1;
+        let mut text = String::new();
+        loop {
+            match self.next_char() {
+                Some('\\') => match self.next_char() {
+                    Some(c) => text.push(c),
+                    None => break,
+                },
+                Some(c) if c == quote => return Ok(Expression::Literal(Value::Text(text))),
+                Some(c) => text.push(c),
+                None => break,
+            }
+        }
+        self.index = start;
+        Err(self.error("文字列が閉じていません".to_string()))
+    }
+    // path、関数の呼び出し、true / false
+    fn parse_name(&mut self) -> Result<Expression, ParseError> {
+        let start = self.index;
+        while self
+            .peek_char()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.')
+        {
+            self.index += 1;
+        }
+        let name: String = self.chars[start..self.index].iter().collect();
+        if name.ends_with('.') || name.contains("..") {
+            return Err(ParseError {
+                position: start + 1,
+                message: format!("'{}' は path として正しくありません", name),
+            });
+        }
+        match name.as_str() {
+            "true" => return Ok(Expression::Literal(Value::Bool(true))),
+            "false" => return Ok(Expression::Literal(Value::Bool(false))),
+            _ => {}
+        }
+        if !self.eat("(") {
+            return Ok(Expression::Path(name));
+        }
+        let Some(function) = Function::find(name.as_str()) else {
+            return Err(ParseError {
+                position: start + 1,
+                message: format!("'{}' という関数はありません", name),
+            });
+        };
+        let mut args = vec![];
+        if !self.eat(")") {
+            loop {
+                args.push(self.parse_expression()?);
+                if self.eat(")") {
+                    break;
+                }
+                self.expect(",")?;
+            }
+        }
+        let (min, max) = function.arity();
+        if args.len() < min || args.len() > max {
+            let count = if min == max {
+                format!("{}", min)
+            } else {
+                format!("{} から {}", min, max)
+            };
+            return Err(ParseError {
+                position: start + 1,
+                message: format!("{} の引数は {} 個です", name, count),
+            });
+        }
+        Ok(Expression::Call(function, args))
+    }
+}
@@ -1,13 +1,22 @@
 use crate::binder::element_manager::ElementManager;
-use crate::content::{ColumnStyle, StringBinder, TableContentState, TextAnchorType};
-use crate::figure::part_rect::{ButtonType, MinimizeOption, PartRect, ShowContentOption};
+use crate::binder::ContentManager;
+use crate::content::{ColumnStyle, LogContentState, TableContentState};
+use crate::figure::part_rect::{
+    ButtonType, JumpToEndOption, MinimizeOption, PartRect, ShowContentOption,
+};
 use crate::figure::AmountPositionType::{End, Start};
+use crate::figure_builder::Containment;
 use crate::math::{Amount, Point};
+use crate::state_store::StateStore;
 use base_rect::BaseRect;
 
 mod base_rect;
+pub(crate) mod hover;
 pub(crate) mod part_rect;
 
+// default_window のスクロールバーの太さ
+const SCROLL_BAR_THICKNESS: f64 = 10.0;
+
 #[derive(Clone)]
 pub(crate) struct RectLength {
     pub(crate) min: f64,
@@ -18,23 +27,37 @@ pub(crate) struct RectLength {
 }
 
 impl RectLength {
+    // min と max が逆転している場合は min を優先する
+    pub(crate) fn constrain(&self, length: f64) -> f64 {
+        length.min(self.max).max(self.min)
+    }
     pub(crate) fn value(&self) -> f64 {
-        self.amount.value().max(self.min)
+        self.constrain(self.amount.value())
     }
     pub(crate) fn fix(&mut self) {
-        self.amount.base = (self.amount.base + self.amount.delta).max(self.min);
+        self.amount.base = self.constrain(self.amount.base + self.amount.delta);
         self.amount.delta = 0.0;
     }
     pub(crate) fn delta_constraint(&mut self) {
-        if self.amount.base + self.amount.delta < self.min {
-            self.amount.delta = self.min - self.amount.base;
+        self.amount.delta = self.constrain(self.amount.value()) - self.amount.base;
+    }
+    pub(crate) fn reset(&mut self) {
+        self.amount = Amount::new(self.default);
+    }
+    pub(crate) fn new_with_min_max(length: f64, min_length: f64, max_length: f64) -> RectLength {
+        RectLength {
+            min: min_length,
+            max: max_length,
+            default: length,
+            amount: Amount::new(length),
+            is_fixed: false,
         }
     }
     pub(crate) fn new_with_min(length: f64, min_length: f64) -> RectLength {
         RectLength {
             min: min_length,
-            max: 0.0,
-            default: 0.0,
+            max: f64::INFINITY,
+            default: length,
             amount: Amount::new(length),
             is_fixed: false,
         }
@@ -42,6 +65,7 @@ impl RectLength {
 }
 
 #[derive(Clone)]
+#[allow(dead_code)]
 pub(crate) enum AmountPositionType {
     Start,
     End,
@@ -50,6 +74,7 @@ pub(crate) enum AmountPositionType {
 }
 
 #[derive(Clone, Debug)]
+#[allow(dead_code)]
 pub(crate) enum PartType {
     Ignore,
     Button(ButtonType),
@@ -60,6 +85,7 @@ pub(crate) enum PartType {
     ScrollBarX(ScrollBarState),
     ScrollBarY(ScrollBarState),
     TableContent(TableContentState),
+    LogContent(LogContentState),
 }
 
 #[derive(Clone, Debug)]
@@ -72,6 +98,57 @@ pub(crate) struct ScrollBarState {
     start_amount: Amount,
     percentage: f64,
     length: f64,
+    // 直前の update で使った内容と表示領域の長さ
+    content_length: f64,
+    scrollable_length: f64,
+    // 溝を押している間だけ値を持つ
+    paging: Option<Paging>,
+    // 末尾に追従するモードの時だけ値を持つ
+    pub(crate) follow_tail: Option<FollowTail>,
+}
+
+// 行が増えた時、末尾を見ていれば末尾に留まり、遡っていれば位置を保って未読の行を数える
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FollowTail {
+    row_count: usize,
+    pub(crate) unread_count: usize,
+}
+
+// 溝を押し続けた時のページ送り
+#[derive(Clone, Debug)]
+struct Paging {
+    // -1.0 で先頭側、1.0 で末尾側
+    direction: f64,
+    // 溝の先頭から押した位置まで。つまみがここに届いたら止める
+    target: f64,
+    frames: u32,
+}
+
+// WheelEvent.deltaMode に対応する
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum DeltaMode {
+    Pixel,
+    Line,
+    Page,
+}
+
+impl DeltaMode {
+    // 1 行分の px
+    const LINE_HEIGHT: f64 = 20.0;
+    pub(crate) fn from_value(value: u32) -> DeltaMode {
+        match value {
+            1 => DeltaMode::Line,
+            2 => DeltaMode::Page,
+            _ => DeltaMode::Pixel,
+        }
+    }
+    fn to_pixel(self, delta: f64, page_length: f64) -> f64 {
+        match self {
+            DeltaMode::Pixel => delta,
+            DeltaMode::Line => delta * DeltaMode::LINE_HEIGHT,
+            DeltaMode::Page => delta * page_length,
+        }
+    }
 }
 
 impl ScrollBarState {
@@ -83,7 +160,99 @@ impl ScrollBarState {
             },
             percentage: 0.0,
             length: 0.0,
+            content_length: 0.0,
+            scrollable_length: 0.0,
+            paging: None,
+            follow_tail: None,
+        }
+    }
+    // 押し始めてから 1 ページ目の繰り返しまでと、その後の間隔（フレーム数）
+    const PAGING_DELAY_FRAMES: u32 = 24;
+    const PAGING_INTERVAL_FRAMES: u32 = 4;
+    // つまみの位置を start にして、percentage も合わせる。動かなかった時は false
+    fn set_start(&mut self, start: f64) -> bool {
+        let full_delta = self.scrollable_length - self.length;
+        if full_delta <= 0.0 {
+            return false;
+        }
+        let start = start.clamp(0.0, full_delta);
+        if start == self.start_amount.value() {
+            return false;
         }
+        self.start_amount = Amount::new(start);
+        self.percentage = start / full_delta;
+        true
+    }
+    // つまみをつかんだ位置から delta px 動かす。動ける幅が無い時は先頭にいるとみなす
+    fn drag(&mut self, delta: f64, parent_length: f64) {
+        self.start_amount.delta = delta;
+        let full_delta = parent_length - self.length;
+        self.percentage = if full_delta > 0.0 {
+            (self.start_amount.value() / full_delta).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+    }
+    // 内容を delta px だけ動かす。はみ出していない時やこれ以上動かない時は false
+    fn scroll_by(&mut self, delta: f64) -> bool {
+        if self.length <= 0.0 || delta == 0.0 {
+            return false;
+        }
+        let full_delta = self.scrollable_length - self.length;
+        let overflow = self.content_length - self.scrollable_length;
+        self.set_start(self.start_amount.value() + delta * full_delta / overflow)
+    }
+    // 内容の position px の位置を表示領域の先頭にする。NaN の時は動かさない
+    fn scroll_to(&mut self, position: f64) -> bool {
+        if self.length <= 0.0 || position.is_nan() {
+            return false;
+        }
+        let full_delta = self.scrollable_length - self.length;
+        let overflow = self.content_length - self.scrollable_length;
+        self.set_start(position * full_delta / overflow)
+    }
+    // 溝の position を押した。つまみより前なら先頭側、後ろなら末尾側に 1 ページ送る
+    fn start_paging(&mut self, position: f64) -> bool {
+        let start = self.start_amount.value();
+        let direction = if position < start {
+            -1.0
+        } else if position > start + self.length {
+            1.0
+        } else {
+            return false;
+        };
+        self.paging = Some(Paging {
+            direction,
+            target: position,
+            frames: 0,
+        });
+        self.scroll_by(direction * self.scrollable_length);
+        true
+    }
+    // 毎フレーム呼ぶ。押し続けている間は、つまみが押した位置に届くまでページ送りを繰り返す
+    fn repeat_paging(&mut self) -> bool {
+        let Some(paging) = self.paging.as_mut() else {
+            return false;
+        };
+        paging.frames += 1;
+        if paging.frames < ScrollBarState::PAGING_DELAY_FRAMES
+            || (paging.frames - ScrollBarState::PAGING_DELAY_FRAMES)
+                % ScrollBarState::PAGING_INTERVAL_FRAMES
+                != 0
+        {
+            return false;
+        }
+        let (direction, target) = (paging.direction, paging.target);
+        let start = self.start_amount.value();
+        if (direction < 0.0 && start <= target)
+            || (direction > 0.0 && start + self.length >= target)
+        {
+            return false;
+        }
+        self.scroll_by(direction * self.scrollable_length)
+    }
+    fn scroll_by_wheel(&mut self, delta: f64, delta_mode: DeltaMode) -> bool {
+        self.scroll_by(delta_mode.to_pixel(delta, self.scrollable_length))
     }
     fn update_delta_in_constraint(&mut self, min: f64, max: f64) {
         let value = self.start_amount.value();
@@ -93,8 +262,44 @@ impl ScrollBarState {
             self.start_amount.delta = max - self.start_amount.base;
         }
     }
+    // 表示領域の先頭にある内容の位置（px）
+    pub(crate) fn scroll_position(&self) -> f64 {
+        if self.length <= 0.0 {
+            return 0.0;
+        }
+        let full_delta = self.scrollable_length - self.length;
+        self.start_amount.value() / full_delta * (self.content_length - self.scrollable_length)
+    }
+    // はみ出していない時も末尾にいるとみなす
+    pub(crate) fn is_at_end(&self) -> bool {
+        self.length <= 0.0 || self.percentage >= 1.0
+    }
+    // 描画した行数を知らせる。末尾にいない間に増えた分を未読とする
+    pub(crate) fn update_row_count(&mut self, row_count: usize) {
+        let is_at_end = self.is_at_end();
+        if let Some(follow_tail) = self.follow_tail.as_mut() {
+            if is_at_end {
+                follow_tail.unread_count = 0;
+            } else {
+                follow_tail.unread_count += row_count.saturating_sub(follow_tail.row_count);
+            }
+            follow_tail.row_count = row_count;
+        }
+    }
     fn update(&mut self, content: f64, scrollable: f64) {
-        if content <= scrollable {
+        // 末尾に追従する時は percentage ではなく内容の位置を保つ
+        if self.follow_tail.is_some() && content > scrollable {
+            self.percentage = if self.is_at_end() {
+                1.0
+            } else {
+                let position = self.percentage * (self.content_length - self.scrollable_length);
+                (position / (content - scrollable)).min(1.0)
+            };
+        }
+        self.content_length = content;
+        self.scrollable_length = scrollable;
+        // 最小化して溝が無くなった時もつまみを消す
+        if content <= scrollable || scrollable <= 0.0 {
             self.length = 0.0;
         } else {
             self.length = scrollable * scrollable / content;
@@ -106,21 +311,43 @@ impl ScrollBarState {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum WindowState {
+    Normal,
+    Minimized,
+    Maximized,
+}
+
+// 最小化・最大化する直前の位置と大きさ
+#[derive(Clone, Debug)]
+pub(crate) struct Geometry {
+    pub(crate) x: f64,
+    pub(crate) y: f64,
+    pub(crate) width: f64,
+    pub(crate) height: f64,
+}
+
 pub(crate) struct Figure {
     pub(crate) base_rect: BaseRect,
     pub(crate) parts: Vec<PartRect>,
     pub(crate) is_grabbed: bool,
     pub(crate) is_pushed: bool,
     pub(crate) is_initialized: bool,
-    pub(crate) group_index: usize,
+    // 閉じるボタンが押された。Binder が次の mouse_up で削除する
+    pub(crate) is_close_requested: bool,
+    pub(crate) window_state: WindowState,
+    // Normal 以外の時だけ値を持つ
+    pub(crate) saved_geometry: Option<Geometry>,
+    pub(crate) figure_id: usize,
 }
 
 impl Figure {
-    pub(crate) fn button_pressed(&mut self, x: f64, y: f64, element_manager: &ElementManager) {
+    pub(crate) fn button_pressed(&mut self, x: f64, y: f64, element_manager: &mut ElementManager) {
         self.is_pushed = false;
         let cloned_base_rect = self.base_rect.clone();
         if let Some(found_parts) = self.parts.iter_mut().find(|parts| parts.is_pushed) {
             found_parts.is_pushed = false;
+            found_parts.stop_paging();
             if !found_parts.is_inner(x, y, &cloned_base_rect) {
                 return;
             }
@@ -129,48 +356,99 @@ impl Figure {
                     ButtonType::Minimize(minimize_option) => {
                         minimize_option.minimize_window(self);
                     }
+                    ButtonType::Maximize(maximize_option) => {
+                        maximize_option.maximize_window(self, element_manager);
+                    }
                     ButtonType::ShowContent(show_content_option) => {
                         show_content_option.adjust_to_show_content(self, element_manager);
+                        self.window_state = WindowState::Normal;
+                        self.saved_geometry = None;
+                    }
+                    ButtonType::Close => {
+                        self.is_close_requested = true;
+                    }
+                    ButtonType::JumpToEnd(..) => {
+                        self.scroll_to(
+                            &Point {
+                                x: f64::NAN,
+                                y: f64::INFINITY,
+                            },
+                            element_manager,
+                        );
                     }
                 }
+                // 大きさが変わってはみ出した分を戻す
+                self.base_rect.clamp_position(element_manager);
             }
         };
     }
 
-    pub(crate) fn adjust(&mut self, element_manager: &ElementManager) {
+    // pointer_cancel 用。押していたボタンを押さなかったことにする
+    pub(crate) fn release_push(&mut self) {
+        self.is_pushed = false;
+        for parts in self.parts.iter_mut() {
+            parts.is_pushed = false;
+            parts.stop_paging();
+        }
+    }
+
+    // 溝を押し続けている間のページ送り。Binder::update から毎フレーム呼ぶ
+    pub(crate) fn repeat_paging(&mut self) -> bool {
+        if !self.is_pushed {
+            return false;
+        }
+        self.parts
+            .iter_mut()
+            .filter(|parts| parts.is_pushed)
+            .any(|parts| parts.repeat_paging())
+    }
+
+    // 表の値の変化を反映する。Binder::update から毎フレーム呼ぶ
+    pub(crate) fn refresh_content(
+        &mut self,
+        element_manager: &mut ElementManager,
+        content_manager: &ContentManager,
+    ) -> bool {
+        let mut is_changed = false;
+        for parts in self.parts.iter_mut() {
+            is_changed |= parts.refresh_content(element_manager, content_manager);
+        }
+        is_changed
+    }
+
+    pub(crate) fn take_template_errors(&mut self) -> Vec<String> {
+        self.parts
+            .iter_mut()
+            .flat_map(|parts| parts.take_template_errors())
+            .collect()
+    }
+
+    pub(crate) fn adjust(&mut self, element_manager: &mut ElementManager) {
         if !self.is_initialized {
-            let mut found_show_content_option = None;
-            self.parts.iter().find(|parts| {
-                if let PartType::Button(button_type) = &parts.part_type {
-                    if let ButtonType::ShowContent(show_content_option) = button_type {
-                        found_show_content_option = Some(show_content_option.clone());
-                        true
-                    } else {
-                        false
-                    }
+            let found_show_content_option = self.parts.iter().find_map(|parts| {
+                if let PartType::Button(ButtonType::ShowContent(show_content_option)) =
+                    &parts.part_type
+                {
+                    Some(show_content_option.clone())
                 } else {
-                    false
+                    None
                 }
             });
-            if found_show_content_option.is_some() {
-                found_show_content_option
-                    .unwrap()
-                    .adjust_to_show_content(self, element_manager);
+            if let Some(show_content_option) = found_show_content_option {
+                show_content_option.adjust_to_show_content(self, element_manager);
             }
+            // 作成時の位置や、内容に合わせて広げた分がはみ出していれば戻す
+            self.base_rect.clamp_position(element_manager);
             self.is_initialized = true;
         }
-        let group_element = &element_manager.figure_groups[self.group_index];
-        group_element
-            .set_attribute(
-                "transform",
-                format!(
-                    "translate({}, {})",
-                    self.base_rect.x_amount.value(),
-                    self.base_rect.y_amount.value()
-                )
-                .as_str(),
-            )
-            .unwrap()
+        let group_element = element_manager.figure_groups[&self.figure_id];
+        let transform = element_manager.camera.figure_transform(
+            self.base_rect.x_amount.value(),
+            self.base_rect.y_amount.value(),
+        );
+        element_manager
+            .backend
+            .set_attribute(group_element, "transform", transform.as_str())
     }
     pub(crate) fn new_log_window_dev(
         title: &str,
@@ -181,50 +459,35 @@ impl Figure {
         element_manager: &mut ElementManager,
     ) -> Figure {
         let container = element_manager.get_container();
-        let group_index = element_manager.create_figure_group(&container);
-        let mut table_content_state = TableContentState::new(table_content_token);
-        table_content_state.tbody_data = vec![
-            vec![
-                StringBinder::new_with_str("とっても長いログのテキストですとっても長いログのテキストですとっても長いログのテキストですとっても長いログのテキストです"),
-            ],
-            vec![
-                StringBinder::new_with_str("HP/MHP"),
-            ],
-            vec![
-                StringBinder::new_with_str("被ダメ"),
-            ],
+        let figure_id = element_manager.create_figure_group(container);
+        let mut table_content_state =
+            TableContentState::new(table_content_token, table_content_token);
+        table_content_state.set_column_styles(ColumnStyle::default_columns());
+        let width = RectLength::new_with_min(1000.0, 1000.0);
+        let height = RectLength::new_with_min(90.0, 30.0);
+        let buttons = vec![
+            ButtonType::Minimize(MinimizeOption {
+                minimized_width: width.min,
+                minimized_height: height.min,
+            }),
+            ButtonType::ShowContent(ShowContentOption {}),
         ];
-        table_content_state.tbody_column_styles = vec![
-            ColumnStyle {
-                defs_id: "".to_string(),
-                text_anchor_type: TextAnchorType::Start,
-                x: 5.0,
-                font_size: 20.0,
-                first_y: 25.0,
-                dy: 25.0,
-            },
-            ColumnStyle {
-                defs_id: "".to_string(),
-                text_anchor_type: TextAnchorType::End,
-                x: 205.0,
-                font_size: 20.0,
-                first_y: 25.0,
-                dy: 25.0,
-            },
-        ];
-        Figure::default_window(
+        let mut figure = Figure::default_window(
             title,
             x,
             y,
-            RectLength::new_with_min(1000.0, 1000.0),
-            RectLength::new_with_min(90.0, 30.0),
+            width,
+            height,
             frame_color,
             5.0,
             25.0,
             PartType::TableContent(table_content_state),
+            buttons,
             element_manager,
-            group_index,
-        )
+            figure_id,
+        );
+        figure.set_follow_tail(5.0, element_manager);
+        figure
     }
     pub(crate) fn new_window_dev(
         title: &str,
@@ -235,54 +498,35 @@ impl Figure {
         element_manager: &mut ElementManager,
     ) -> Figure {
         let container = element_manager.get_container();
-        let group_index = element_manager.create_figure_group(&container);
-        let mut table_content_state = TableContentState::new(table_content_token);
-        table_content_state.tbody_data = vec![
-            vec![
-                StringBinder::new_with_str("行動順"),
-                StringBinder::new_with_str("後攻"),
-            ],
-            vec![
-                StringBinder::new_with_str("HP/MHP"),
-                StringBinder::new_with_str("50/50"),
-            ],
-            vec![
-                StringBinder::new_with_str("被ダメ"),
-                StringBinder::new_with_str("5"),
-            ],
-        ];
-        table_content_state.tbody_column_styles = vec![
-            ColumnStyle {
-                defs_id: "".to_string(),
-                text_anchor_type: TextAnchorType::Start,
-                x: 5.0,
-                font_size: 20.0,
-                first_y: 25.0,
-                dy: 25.0,
-            },
-            ColumnStyle {
-                defs_id: "".to_string(),
-                text_anchor_type: TextAnchorType::End,
-                x: 205.0,
-                font_size: 20.0,
-                first_y: 25.0,
-                dy: 25.0,
-            },
+        let figure_id = element_manager.create_figure_group(container);
+        let mut table_content_state =
+            TableContentState::new(table_content_token, table_content_token);
+        table_content_state.set_column_styles(ColumnStyle::default_columns());
+        let width = RectLength::new_with_min(200.0, 180.0);
+        let height = RectLength::new_with_min(300.0, 30.0);
+        let buttons = vec![
+            ButtonType::Minimize(MinimizeOption {
+                minimized_width: width.min,
+                minimized_height: height.min,
+            }),
+            ButtonType::ShowContent(ShowContentOption {}),
         ];
         Figure::default_window(
             title,
             x,
             y,
-            RectLength::new_with_min(200.0, 180.0),
-            RectLength::new_with_min(300.0, 30.0),
+            width,
+            height,
             frame_color,
             5.0,
             25.0,
             PartType::TableContent(table_content_state),
+            buttons,
             element_manager,
-            group_index,
+            figure_id,
         )
     }
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn default_window(
         title: &str,
         start_x: f64,
@@ -293,18 +537,52 @@ impl Figure {
         margin: f64,
         title_height: f64,
         content_part_type: PartType,
+        buttons: Vec<ButtonType>,
         element_manager: &mut ElementManager,
-        group_index: usize,
+        figure_id: usize,
     ) -> Figure {
         let offset_x = 0.0;
         let offset_y = title_height;
-        let scroll_bar_thickness = 10.0;
         let button_size = 20.0;
-        let min_width = width.min;
-        let min_height = height.min;
-        // TODO
-        // clone しなくてよくはならないか
-        let group_element = &mut element_manager.figure_groups[group_index].clone();
+        let group_element = element_manager.figure_groups[&figure_id];
+        let base_element_index =
+            element_manager.create_element_with_defs_id(group_element, "def-default-window-base");
+        let mut parts = vec![
+            PartRect::default_scrollable(
+                margin,
+                offset_x,
+                offset_y,
+                SCROLL_BAR_THICKNESS,
+                "white",
+                content_part_type,
+                element_manager,
+                group_element,
+            ),
+            PartRect::default_title_bg(
+                title,
+                margin,
+                offset_y,
+                frame_color,
+                element_manager.create_element_with_symbol_id(
+                    group_element,
+                    "def-default-window-title-background",
+                ),
+            ),
+        ];
+        // buttons は左から順に並べ、最後の要素を右端に置く
+        let button_count = buttons.len();
+        for (n, button_type) in buttons.into_iter().enumerate() {
+            let offset = (button_count - n) as f64 * (margin + button_size);
+            parts.push(PartRect::default_button(
+                (-offset, End),
+                (margin, Start),
+                button_size,
+                "white",
+                element_manager.create_element_with_group(group_element),
+                element_manager,
+                button_type,
+            ));
+        }
         Figure {
             base_rect: BaseRect {
                 x_amount: Amount::new(start_x),
@@ -312,64 +590,148 @@ impl Figure {
                 width,
                 height,
                 color: frame_color.to_string(),
-                element_index: element_manager
-                    .create_element_with_defs_id(&group_element, "def-default-window-base"),
+                element_index: base_element_index,
                 is_grabbed: false,
                 x_fixed: false,
                 y_fixed: false,
+                aspect_ratio: None,
+                containment: Containment::Inside,
+                resize_handle_thickness: margin,
+                grabbed_handle: None,
+                handle_element_indexes: BaseRect::create_resize_handles(
+                    element_manager,
+                    group_element,
+                ),
                 // TODO
                 // 現在は base_rect は全て Expand で記述されているが、そうとも限らない
                 // 他の要件が明らかになったところで実装を見直す
                 part_type: PartType::Expand,
             },
-            parts: vec![
-                PartRect::default_scrollable(
-                    margin,
-                    offset_x,
-                    offset_y,
-                    scroll_bar_thickness,
-                    "white",
-                    content_part_type,
-                    element_manager,
-                    group_element,
-                ),
-                PartRect::default_title_bg(
-                    title,
-                    margin,
-                    offset_y,
-                    frame_color,
-                    element_manager.create_element_with_symbol_id(
-                        &group_element,
-                        "def-default-window-title-background",
-                    ),
-                ),
-                PartRect::default_button(
-                    (-margin - button_size - margin - button_size, End),
-                    (margin, Start),
-                    button_size,
-                    "white",
-                    element_manager.create_element_with_group(&group_element),
-                    element_manager,
-                    ButtonType::Minimize(MinimizeOption {
-                        minimized_width: min_width,
-                        minimized_height: min_height,
-                    }),
-                ),
-                PartRect::default_button(
-                    (-margin - button_size, End),
-                    (margin, Start),
-                    button_size,
-                    "white",
-                    element_manager.create_element_with_group(&group_element),
-                    element_manager,
-                    ButtonType::ShowContent(ShowContentOption {}),
-                ),
-            ],
+            parts,
             is_grabbed: false,
             is_pushed: false,
             is_initialized: false,
-            group_index,
+            is_close_requested: false,
+            window_state: WindowState::Normal,
+            saved_geometry: None,
+            figure_id,
+        }
+    }
+
+    // 最初の Scrollable を末尾に追従させ、遡っている間に表示する末尾へ移動するボタンを置く
+    pub(crate) fn set_follow_tail(&mut self, margin: f64, element_manager: &mut ElementManager) {
+        let Some(index) = self.scrollable_index() else {
+            return;
+        };
+        self.parts[index].set_follow_tail(true);
+        // スクロールバーと隙間を空けて右下に置く
+        let button_size = 20.0;
+        let gap = 4.0;
+        let offset = margin + SCROLL_BAR_THICKNESS + gap + button_size;
+        let group_element = element_manager.figure_groups[&self.figure_id];
+        let element_index = element_manager.create_element_with_group(group_element);
+        let count_text = element_manager
+            .backend
+            .create_element(group_element, "text");
+        for (name, value) in [
+            ("fill", "#333"),
+            ("font-size", "14"),
+            ("text-anchor", "end"),
+        ] {
+            element_manager
+                .backend
+                .set_attribute(count_text, name, value);
         }
+        self.parts.push(PartRect::default_button(
+            (-offset, End),
+            (-offset, End),
+            button_size,
+            "white",
+            element_index,
+            element_manager,
+            ButtonType::JumpToEnd(JumpToEndOption {
+                count_text,
+                unread_count: 0,
+            }),
+        ));
+    }
+
+    // ログの内容を持たない Figure では false
+    pub(crate) fn push_line(&mut self, line: &str) -> bool {
+        self.parts.iter_mut().any(|parts| parts.push_line(line))
+    }
+
+    // 表の内容を取得する時の key
+    pub(crate) fn content_key(&self) -> Option<&str> {
+        self.parts.iter().find_map(|parts| parts.content_key())
+    }
+
+    pub(crate) fn unread_count(&self) -> Option<usize> {
+        self.parts.iter().find_map(|parts| parts.unread_count())
+    }
+
+    pub(crate) fn adjust_jump_to_end(&mut self, element_manager: &mut ElementManager) {
+        let Some(unread_count) = self.unread_count() else {
+            return;
+        };
+        for parts in self.parts.iter_mut() {
+            parts.adjust_jump_to_end(unread_count, &self.base_rect, element_manager);
+        }
+    }
+
+    // Normal から別の状態に移る時だけ記録する（最小化から最大化した時は最初の値を残す）
+    pub(crate) fn save_geometry(&mut self) {
+        if self.window_state == WindowState::Normal {
+            self.saved_geometry = Some(Geometry {
+                x: self.base_rect.x_amount.value(),
+                y: self.base_rect.y_amount.value(),
+                width: self.base_rect.width_value(),
+                height: self.base_rect.height_value(),
+            });
+        }
+    }
+    pub(crate) fn set_geometry(&mut self, geometry: &Geometry) {
+        self.base_rect.x_amount = Amount::new(geometry.x);
+        self.base_rect.y_amount = Amount::new(geometry.y);
+        self.base_rect.width.amount = Amount::new(geometry.width);
+        self.base_rect.height.amount = Amount::new(geometry.height);
+    }
+    pub(crate) fn restore_geometry(&mut self) {
+        if let Some(geometry) = self.saved_geometry.take() {
+            self.set_geometry(&geometry);
+        }
+        self.window_state = WindowState::Normal;
+    }
+    // 大きさだけ戻す。最小化している間に動かした位置はそのまま使う
+    pub(crate) fn restore_size(&mut self) {
+        if let Some(geometry) = self.saved_geometry.take() {
+            self.set_geometry(&Geometry {
+                x: self.base_rect.x_amount.value(),
+                y: self.base_rect.y_amount.value(),
+                ..geometry
+            });
+        }
+        self.window_state = WindowState::Normal;
+    }
+
+    // 最小化・最大化も解除して、作成時の大きさに戻す
+    pub(crate) fn reset_size(&mut self) {
+        self.base_rect.width.reset();
+        self.base_rect.height.reset();
+        self.window_state = WindowState::Normal;
+        self.saved_geometry = None;
+    }
+
+    // この Figure が ElementManager に登録した要素の index
+    pub(crate) fn element_indexes(&self) -> Vec<usize> {
+        let mut element_indexes = vec![self.base_rect.element_index];
+        for (_, element_index) in self.base_rect.handle_element_indexes.iter() {
+            element_indexes.push(*element_index);
+        }
+        for parts in self.parts.iter() {
+            parts.collect_element_indexes(&mut element_indexes);
+        }
+        element_indexes
     }
 
     pub(crate) fn update_base(&mut self) {
@@ -386,6 +748,17 @@ impl Figure {
     pub(crate) fn grab(&mut self, x: f64, y: f64) -> bool {
         // 自分を更新しながら part_rect.is_inner で base_rect を参照しているので clone 不可避
         let clone = self.base_rect.clone();
+        // サイズ変更の領域は、重なっている PartRect より優先する
+        let handle = self.base_rect.resize_handle(
+            x - self.base_rect.x_amount.value(),
+            y - self.base_rect.y_amount.value(),
+        );
+        if handle.is_some() {
+            self.base_rect.grabbed_handle = handle;
+            self.base_rect.is_grabbed = true;
+            self.is_grabbed = true;
+            return true;
+        }
         // TODO
         // 重なり順で最後の要素を掴みたい（プッシュしたい）と思っているので filter().last() を使用しているけど妥当か
         // reverse() 的なことをするのと基本的には同じである
@@ -414,16 +787,11 @@ impl Figure {
         element_manager: &ElementManager,
     ) {
         if self.is_grabbed {
-            let drag_start_point = &Point {
-                x: raw_drag_start_point.x - self.base_rect.x_amount.base,
-                y: raw_drag_start_point.y - self.base_rect.y_amount.base,
-            };
             if let Some(parts) = self.parts.iter_mut().find(|parts| parts.is_grabbed) {
                 let parent_width = parts.width_value(&self.base_rect);
                 let parent_height = parts.height_value(&self.base_rect);
                 if let PartType::Drag | PartType::Title(..) = parts.part_type {
-                    self.base_rect
-                        .move_xy(&drag_start_point, &delta_point, true);
+                    self.base_rect.move_xy(delta_point, true, element_manager);
                 } else if let PartType::Scrollable = parts.part_type {
                     if let Some(internal) = parts
                         .internal_part_rect
@@ -432,73 +800,20 @@ impl Figure {
                     {
                         match &mut internal.part_type {
                             PartType::ScrollBarX(scroll_bar_state) => {
-                                scroll_bar_state.start_amount.delta = delta_point.x;
-                                let full_delta = parent_width - scroll_bar_state.length;
-                                scroll_bar_state.percentage =
-                                    scroll_bar_state.start_amount.value() / full_delta;
+                                scroll_bar_state.drag(delta_point.x, parent_width);
                             }
                             PartType::ScrollBarY(scroll_bar_state) => {
-                                scroll_bar_state.start_amount.delta = delta_point.y;
-                                let full_delta = parent_height - scroll_bar_state.length;
-                                scroll_bar_state.percentage =
-                                    scroll_bar_state.start_amount.value() / full_delta;
+                                scroll_bar_state.drag(delta_point.y, parent_height);
                             }
                             _ => {}
                         }
                     }
                 }
             } else {
-                self.base_rect
-                    .move_xy(&drag_start_point, &delta_point, false);
+                self.base_rect.move_xy(delta_point, false, element_manager);
             }
             // スクロールバーを触っていない状態でも、スクロールバーはスタート位置と長さの再計算が必要
-            for parts in self.parts.iter_mut() {
-                if let PartType::Scrollable = parts.part_type {
-                    let mut internal_max_width: f64 = 0.0;
-                    let mut internal_max_height: f64 = 0.0;
-                    let scrollable_width = parts.width_value(&self.base_rect);
-                    let scrollable_height = parts.height_value(&self.base_rect);
-                    for internal in parts.internal_part_rect.iter() {
-                        match internal.part_type {
-                            PartType::ScrollBarX(..) | PartType::ScrollBarY(..) => {}
-                            _ => {
-                                internal_max_width =
-                                    internal_max_width.max(internal.width_value(&self.base_rect));
-                                internal_max_height =
-                                    internal_max_height.max(internal.height_value(&self.base_rect));
-                                if let Some(sibling) = element_manager.elements
-                                    [internal.element_index]
-                                    .next_element_sibling()
-                                {
-                                    if sibling.tag_name() == "g" {
-                                        internal_max_width = internal_max_width.max(
-                                            sibling.get_bounding_client_rect().width()
-                                                / element_manager.scale
-                                                + 10.0,
-                                        );
-                                        internal_max_height = internal_max_height.max(
-                                            sibling.get_bounding_client_rect().height()
-                                                / element_manager.scale
-                                                + 10.0,
-                                        );
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    for internal in parts.internal_part_rect.iter_mut() {
-                        match &mut internal.part_type {
-                            PartType::ScrollBarX(scroll_bar_state) => {
-                                scroll_bar_state.update(internal_max_width, scrollable_width);
-                            }
-                            PartType::ScrollBarY(scroll_bar_state) => {
-                                scroll_bar_state.update(internal_max_height, scrollable_height);
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            }
+            self.update_scroll_bars(element_manager);
         } else if self.is_pushed {
             if let Some(found_parts) = self.parts.iter_mut().find(|parts| parts.is_pushed) {
                 if !found_parts.is_inner(
@@ -508,6 +823,7 @@ impl Figure {
                 ) {
                     self.is_pushed = false;
                     found_parts.is_pushed = false;
+                    found_parts.stop_paging();
                 }
             } else {
                 self.is_pushed = false;
@@ -515,28 +831,108 @@ impl Figure {
         }
     }
 
+    pub(crate) fn update_scroll_bars(&mut self, element_manager: &ElementManager) {
+        for parts in self.parts.iter_mut() {
+            if let PartType::Scrollable = parts.part_type {
+                parts.update_scroll_bars(&self.base_rect, element_manager);
+            }
+        }
+    }
+
+    // (x, y) にある Scrollable をスクロールする。スクロールしなかった時は false
+    pub(crate) fn wheel(
+        &mut self,
+        x: f64,
+        y: f64,
+        delta: &Point,
+        delta_mode: DeltaMode,
+        element_manager: &ElementManager,
+    ) -> bool {
+        let Some(index) = self.parts.iter().rposition(|parts| {
+            matches!(parts.part_type, PartType::Scrollable) && parts.is_inner(x, y, &self.base_rect)
+        }) else {
+            return false;
+        };
+        self.scroll_part(index, delta, element_manager, |scroll_bar_state, delta| {
+            scroll_bar_state.scroll_by_wheel(delta, delta_mode)
+        })
+    }
+
+    // 最初の Scrollable の内容を delta px 動かす
+    pub(crate) fn scroll_by(&mut self, delta: &Point, element_manager: &ElementManager) -> bool {
+        let Some(index) = self.scrollable_index() else {
+            return false;
+        };
+        self.scroll_part(index, delta, element_manager, ScrollBarState::scroll_by)
+    }
+
+    // 最初の Scrollable の内容の position px の位置を表示領域の左上にする
+    pub(crate) fn scroll_to(&mut self, position: &Point, element_manager: &ElementManager) -> bool {
+        let Some(index) = self.scrollable_index() else {
+            return false;
+        };
+        self.scroll_part(index, position, element_manager, ScrollBarState::scroll_to)
+    }
+
+    fn scrollable_index(&self) -> Option<usize> {
+        self.parts
+            .iter()
+            .position(|parts| matches!(parts.part_type, PartType::Scrollable))
+    }
+
+    // ScrollBarX には value.x を、ScrollBarY には value.y を渡す
+    fn scroll_part(
+        &mut self,
+        index: usize,
+        value: &Point,
+        element_manager: &ElementManager,
+        scroll: impl Fn(&mut ScrollBarState, f64) -> bool,
+    ) -> bool {
+        self.update_scroll_bars(element_manager);
+        let mut is_scrolled = false;
+        for internal in self.parts[index].internal_part_rect.iter_mut() {
+            match &mut internal.part_type {
+                PartType::ScrollBarX(scroll_bar_state) => {
+                    is_scrolled |= scroll(scroll_bar_state, value.x);
+                }
+                PartType::ScrollBarY(scroll_bar_state) => {
+                    is_scrolled |= scroll(scroll_bar_state, value.y);
+                }
+                _ => {}
+            }
+        }
+        is_scrolled
+    }
+
     pub(crate) fn is_inner(&self, raw_x: f64, raw_y: f64) -> bool {
         let x = raw_x - self.base_rect.x_amount.value();
         let y = raw_y - self.base_rect.y_amount.value();
         // base_rect からはみ出している PartRect がない前提の実装
         let x_value = self.base_rect.x_value();
+        let y_value = self.base_rect.y_value();
         // マウスポインターの形が変わっても領域外だったりするので
         // スケール変更も考えて外側に 2px は拡大して内部判定を許容
-        if x_value > x + 2.0 {
-            false
-        } else if x_value + self.base_rect.width_value() < x - 2.0 {
-            false
-        } else {
-            let y_value = self.base_rect.y_value();
-            if y_value > y + 2.0 {
-                false
-            } else if y_value + self.base_rect.height_value() < y - 2.0 {
-                false
-            } else {
-                true
-            }
-        }
+        x_value <= x + 2.0
+            && x_value + self.base_rect.width_value() >= x - 2.0
+            && y_value <= y + 2.0
+            && y_value + self.base_rect.height_value() >= y - 2.0
     }
 }
 
-pub(crate) struct TemporaryState {}
+// StringBinder が値を取り出す元
+pub(crate) struct TemporaryState<'a> {
+    pub(crate) store: &'a StateStore,
+    pub(crate) thead: &'a [String],
+    pub(crate) tbody: &'a [Vec<String>],
+    // false の時は thead と tbody の値を式として評価せずにそのまま表示する
+    pub(crate) uses_templates: bool,
+}
+
+impl TemporaryState<'_> {
+    pub(crate) fn cell(&self, row: usize, column: usize) -> Option<&str> {
+        self.tbody
+            .get(row)
+            .and_then(|values| values.get(column))
+            .map(|value| value.as_str())
+    }
+}
@@ -1,8 +1,91 @@
 use crate::binder::element_manager::ElementManager;
 use crate::figure::{PartType, RectLength};
+use crate::figure_builder::Containment;
 use crate::math::{Amount, Point};
+use crate::render_backend::NodeId;
+
+// サイズ変更をつかむ辺と角
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum ResizeHandle {
+    N,
+    S,
+    E,
+    W,
+    NE,
+    NW,
+    SE,
+    SW,
+}
+
+// ResizeHandle が動かす辺。Start は左か上、End は右か下
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum HandleSide {
+    Start,
+    End,
+}
+
+impl ResizeHandle {
+    const ALL: [ResizeHandle; 8] = [
+        ResizeHandle::N,
+        ResizeHandle::S,
+        ResizeHandle::E,
+        ResizeHandle::W,
+        ResizeHandle::NE,
+        ResizeHandle::NW,
+        ResizeHandle::SE,
+        ResizeHandle::SW,
+    ];
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            ResizeHandle::N => "n",
+            ResizeHandle::S => "s",
+            ResizeHandle::E => "e",
+            ResizeHandle::W => "w",
+            ResizeHandle::NE => "ne",
+            ResizeHandle::NW => "nw",
+            ResizeHandle::SE => "se",
+            ResizeHandle::SW => "sw",
+        }
+    }
+    pub(crate) fn cursor(&self) -> &'static str {
+        match self {
+            ResizeHandle::N | ResizeHandle::S => "ns-resize",
+            ResizeHandle::E | ResizeHandle::W => "ew-resize",
+            ResizeHandle::NE | ResizeHandle::SW => "nesw-resize",
+            ResizeHandle::NW | ResizeHandle::SE => "nwse-resize",
+        }
+    }
+    fn x_side(&self) -> Option<HandleSide> {
+        match self {
+            ResizeHandle::W | ResizeHandle::NW | ResizeHandle::SW => Some(HandleSide::Start),
+            ResizeHandle::E | ResizeHandle::NE | ResizeHandle::SE => Some(HandleSide::End),
+            ResizeHandle::N | ResizeHandle::S => None,
+        }
+    }
+    fn y_side(&self) -> Option<HandleSide> {
+        match self {
+            ResizeHandle::N | ResizeHandle::NE | ResizeHandle::NW => Some(HandleSide::Start),
+            ResizeHandle::S | ResizeHandle::SE | ResizeHandle::SW => Some(HandleSide::End),
+            ResizeHandle::E | ResizeHandle::W => None,
+        }
+    }
+    fn from_sides(x_side: Option<HandleSide>, y_side: Option<HandleSide>) -> Option<ResizeHandle> {
+        match (x_side, y_side) {
+            (None, Some(HandleSide::Start)) => Some(ResizeHandle::N),
+            (None, Some(HandleSide::End)) => Some(ResizeHandle::S),
+            (Some(HandleSide::End), None) => Some(ResizeHandle::E),
+            (Some(HandleSide::Start), None) => Some(ResizeHandle::W),
+            (Some(HandleSide::End), Some(HandleSide::Start)) => Some(ResizeHandle::NE),
+            (Some(HandleSide::Start), Some(HandleSide::Start)) => Some(ResizeHandle::NW),
+            (Some(HandleSide::End), Some(HandleSide::End)) => Some(ResizeHandle::SE),
+            (Some(HandleSide::Start), Some(HandleSide::End)) => Some(ResizeHandle::SW),
+            (None, None) => None,
+        }
+    }
+}
 
 #[derive(Clone)]
+#[allow(dead_code)]
 pub(crate) struct BaseRect {
     pub(crate) x_amount: Amount,
     pub(crate) y_amount: Amount,
@@ -13,6 +96,14 @@ pub(crate) struct BaseRect {
     pub(crate) is_grabbed: bool,
     pub(crate) x_fixed: bool,
     pub(crate) y_fixed: bool,
+    // width / height。Some の時はサイズ変更でこの比率を保つ
+    pub(crate) aspect_ratio: Option<f64>,
+    pub(crate) containment: Containment,
+    // 辺から内側に何 px までをサイズ変更の領域とするか
+    pub(crate) resize_handle_thickness: f64,
+    pub(crate) grabbed_handle: Option<ResizeHandle>,
+    // サイズ変更の領域に重ねる透明な rect。角は L 字にするので 2 つずつ持つ
+    pub(crate) handle_element_indexes: Vec<(ResizeHandle, usize)>,
     pub(crate) element_index: usize,
 }
 
@@ -30,74 +121,272 @@ impl BaseRect {
     pub(crate) fn height_value(&self) -> f64 {
         self.height.value()
     }
-    fn near_point_x(&self, x: f64) -> usize {
-        if x < self.width.amount.base / 2.0 {
-            0
+    // 角として扱う辺に沿った長さ
+    fn resize_corner_length(&self) -> f64 {
+        self.resize_handle_thickness * 3.0
+    }
+    // (x, y) は base_rect の左上からの座標
+    pub(crate) fn resize_handle(&self, x: f64, y: f64) -> Option<ResizeHandle> {
+        let thickness = self.resize_handle_thickness;
+        let corner = self.resize_corner_length();
+        let width = self.width_value();
+        let height = self.height_value();
+        let is_left = x < thickness;
+        let is_right = x > width - thickness;
+        let is_top = y < thickness;
+        let is_bottom = y > height - thickness;
+        let is_x_edge = is_left || is_right;
+        let is_y_edge = is_top || is_bottom;
+        if !is_x_edge && !is_y_edge {
+            return None;
+        }
+        let x_side = if is_left || (is_y_edge && x < corner) {
+            Some(HandleSide::Start)
+        } else if is_right || (is_y_edge && x > width - corner) {
+            Some(HandleSide::End)
+        } else {
+            None
+        };
+        let y_side = if is_top || (is_x_edge && y < corner) {
+            Some(HandleSide::Start)
+        } else if is_bottom || (is_x_edge && y > height - corner) {
+            Some(HandleSide::End)
         } else {
-            1
+            None
+        };
+        ResizeHandle::from_sides(x_side, y_side)
+    }
+    // handle の領域を表す (x, y, width, height) の一覧
+    fn handle_rects(&self, handle: ResizeHandle) -> Vec<(f64, f64, f64, f64)> {
+        let thickness = self.resize_handle_thickness;
+        let corner = self.resize_corner_length();
+        let width = self.width_value();
+        let height = self.height_value();
+        let inner_width = (width - corner * 2.0).max(0.0);
+        let inner_height = (height - corner * 2.0).max(0.0);
+        let (x_start, x_end) = (0.0, width - thickness);
+        let (y_start, y_end) = (0.0, height - thickness);
+        match handle {
+            ResizeHandle::N => vec![(corner, y_start, inner_width, thickness)],
+            ResizeHandle::S => vec![(corner, y_end, inner_width, thickness)],
+            ResizeHandle::W => vec![(x_start, corner, thickness, inner_height)],
+            ResizeHandle::E => vec![(x_end, corner, thickness, inner_height)],
+            ResizeHandle::NW => vec![
+                (0.0, y_start, corner, thickness),
+                (x_start, 0.0, thickness, corner),
+            ],
+            ResizeHandle::NE => vec![
+                (width - corner, y_start, corner, thickness),
+                (x_end, 0.0, thickness, corner),
+            ],
+            ResizeHandle::SW => vec![
+                (0.0, y_end, corner, thickness),
+                (x_start, height - corner, thickness, corner),
+            ],
+            ResizeHandle::SE => vec![
+                (width - corner, y_end, corner, thickness),
+                (x_end, height - corner, thickness, corner),
+            ],
         }
     }
-    fn near_point_y(&self, y: f64) -> usize {
-        if y < self.height.amount.base / 2.0 {
-            0
-        } else {
-            1
+    // Figure の g の末尾に、handle 用の rect をまとめた g を追加する
+    pub(crate) fn create_resize_handles(
+        element_manager: &mut ElementManager,
+        group_element: NodeId,
+    ) -> Vec<(ResizeHandle, usize)> {
+        let handle_group = element_manager.backend.create_element(group_element, "g");
+        let mut handle_element_indexes = vec![];
+        for handle in ResizeHandle::ALL {
+            let count = if handle.x_side().is_some() && handle.y_side().is_some() {
+                2
+            } else {
+                1
+            };
+            for _ in 0..count {
+                let element_index = element_manager.create_element(handle_group, "rect");
+                handle_element_indexes.push((handle, element_index));
+            }
+        }
+        handle_element_indexes
+    }
+    // Containment::TitleBarVisible で container 内に残す長さ
+    const VISIBLE_LENGTH: f64 = 40.0;
+    // ウィンドウの左上の点が取れる範囲
+    // タイトルバーは上端にあるので、TitleBarVisible でも上には、はみ出させない
+    fn position_range(&self, length: f64, container_length: f64, is_y: bool) -> (f64, f64) {
+        let (min, max) = match self.containment {
+            Containment::Inside => (0.0, container_length - length),
+            Containment::TitleBarVisible if is_y => {
+                (0.0, container_length - BaseRect::VISIBLE_LENGTH)
+            }
+            Containment::TitleBarVisible => (
+                BaseRect::VISIBLE_LENGTH - length,
+                container_length - BaseRect::VISIBLE_LENGTH,
+            ),
+            Containment::Unconstrained => (f64::NEG_INFINITY, f64::INFINITY),
+        };
+        (min, max.max(min))
+    }
+    // サイズ変更でウィンドウの辺が取れる範囲
+    fn edge_range(&self, container_length: f64) -> (f64, f64) {
+        match self.containment {
+            Containment::Inside => (0.0, container_length),
+            Containment::TitleBarVisible => (0.0, f64::INFINITY),
+            Containment::Unconstrained => (f64::NEG_INFINITY, f64::INFINITY),
         }
     }
-    fn move_x(&mut self, start_x: f64, delta_x: f64, always_fixed: bool) {
+    fn clamp_amount(amount: &mut Amount, (min, max): (f64, f64)) {
+        amount.delta = amount.value().clamp(min, max) - amount.base;
+    }
+    fn move_x(&mut self, delta_x: f64, always_fixed: bool, container_width: f64) {
         if self.width.is_fixed || always_fixed {
             self.x_amount.delta = delta_x;
-            // TODO
-            // constraint の処理をまとめる
-            if (self.x_amount.base + delta_x).is_sign_negative() {
-                self.x_amount.delta = -self.x_amount.base;
-            }
-        } else {
-            let index = self.near_point_x(start_x);
-            if index == 0 {
+            let range = self.position_range(self.width.value(), container_width, false);
+            BaseRect::clamp_amount(&mut self.x_amount, range);
+        } else if let Some(side) = self.grabbed_handle.and_then(|handle| handle.x_side()) {
+            let (min_edge, max_edge) = self.edge_range(container_width);
+            if side == HandleSide::Start {
                 self.width.amount.delta = -delta_x;
                 self.width.delta_constraint();
                 self.x_amount.delta = -self.width.amount.delta;
-                if self.x_amount.value().is_sign_negative() {
-                    self.x_amount.delta = -self.x_amount.base;
+                if self.x_amount.value() < min_edge {
+                    self.x_amount.delta = min_edge - self.x_amount.base;
                     self.width.amount.delta = -self.x_amount.delta;
                 }
             } else {
                 self.width.amount.delta = delta_x;
+                let max_width = max_edge - self.x_amount.value();
+                if self.width.amount.value() > max_width {
+                    self.width.amount.delta = max_width - self.width.amount.base;
+                }
                 self.width.delta_constraint();
             }
         }
     }
-    fn move_y(&mut self, start_y: f64, delta_y: f64, always_fixed: bool) {
+    fn move_y(&mut self, delta_y: f64, always_fixed: bool, container_height: f64) {
         if self.height.is_fixed || always_fixed {
             self.y_amount.delta = delta_y;
-            // TODO
-            // constraint の処理をまとめる
-            if (self.y_amount.base + delta_y).is_sign_negative() {
-                self.y_amount.delta = -self.y_amount.base;
-            }
-        } else {
-            let index = self.near_point_y(start_y);
-            if index == 0 {
+            let range = self.position_range(self.height.value(), container_height, true);
+            BaseRect::clamp_amount(&mut self.y_amount, range);
+        } else if let Some(side) = self.grabbed_handle.and_then(|handle| handle.y_side()) {
+            let (min_edge, max_edge) = self.edge_range(container_height);
+            if side == HandleSide::Start {
                 self.height.amount.delta = -delta_y;
                 self.height.delta_constraint();
                 self.y_amount.delta = -self.height.amount.delta;
-                if self.y_amount.value().is_sign_negative() {
-                    self.y_amount.delta = -self.y_amount.base;
+                if self.y_amount.value() < min_edge {
+                    self.y_amount.delta = min_edge - self.y_amount.base;
                     self.height.amount.delta = -self.y_amount.delta;
                 }
             } else {
                 self.height.amount.delta = delta_y;
+                let max_height = max_edge - self.y_amount.value();
+                if self.height.amount.value() > max_height {
+                    self.height.amount.delta = max_height - self.height.amount.base;
+                }
                 self.height.delta_constraint();
             }
         }
     }
-    pub(crate) fn move_xy(&mut self, start_point: &Point, delta_point: &Point, always_fixed: bool) {
+    // handle をつかんでいない時は、always_fixed でなくてもウィンドウを移動する
+    pub(crate) fn move_xy(
+        &mut self,
+        delta_point: &Point,
+        always_fixed: bool,
+        element_manager: &ElementManager,
+    ) {
+        let always_fixed = always_fixed || self.grabbed_handle.is_none();
+        if !always_fixed && self.keeps_aspect_ratio() {
+            self.resize_keeping_aspect_ratio(delta_point, element_manager);
+            return;
+        }
         if !self.x_fixed {
-            self.move_x(start_point.x, delta_point.x, always_fixed);
+            self.move_x(delta_point.x, always_fixed, element_manager.container_width);
         }
         if !self.y_fixed {
-            self.move_y(start_point.y, delta_point.y, always_fixed);
+            self.move_y(
+                delta_point.y,
+                always_fixed,
+                element_manager.container_height,
+            );
+        }
+    }
+    // container の大きさやウィンドウの大きさが変わった後に、位置を範囲内に戻す
+    pub(crate) fn clamp_position(&mut self, element_manager: &ElementManager) {
+        let range = self.position_range(self.width.value(), element_manager.container_width, false);
+        BaseRect::clamp_amount(&mut self.x_amount, range);
+        let range =
+            self.position_range(self.height.value(), element_manager.container_height, true);
+        BaseRect::clamp_amount(&mut self.y_amount, range);
+    }
+    fn keeps_aspect_ratio(&self) -> bool {
+        self.aspect_ratio.is_some()
+            && !(self.width.is_fixed || self.height.is_fixed || self.x_fixed || self.y_fixed)
+    }
+    // サイズ変更で取れる長さの範囲。handle が動かさない辺は End の側に伸びる
+    fn length_range(
+        &self,
+        length: &RectLength,
+        position: &Amount,
+        side: Option<HandleSide>,
+        container_length: f64,
+    ) -> (f64, f64) {
+        let (min_edge, max_edge) = self.edge_range(container_length);
+        let available = if side == Some(HandleSide::Start) {
+            position.base + length.amount.base - min_edge
+        } else {
+            max_edge - position.base
+        };
+        (length.min, length.max.min(available))
+    }
+    // 変化の割合が大きい方の辺に合わせて、もう一方の辺の長さを決める
+    // 比率を当ててから範囲に収め、収めた辺からもう一方の辺を決め直す。範囲が両立しない時は範囲を優先する
+    fn resize_keeping_aspect_ratio(
+        &mut self,
+        delta_point: &Point,
+        element_manager: &ElementManager,
+    ) {
+        let (Some(ratio), Some(handle)) = (self.aspect_ratio, self.grabbed_handle) else {
+            return;
+        };
+        let (x_side, y_side) = (handle.x_side(), handle.y_side());
+        let resized = |base: f64, delta: f64, side: Option<HandleSide>| match side {
+            Some(HandleSide::Start) => base - delta,
+            Some(HandleSide::End) => base + delta,
+            None => base,
+        };
+        let width = resized(self.width.amount.base, delta_point.x, x_side);
+        let height = resized(self.height.amount.base, delta_point.y, y_side);
+        let width_rate = (width / self.width.amount.base - 1.0).abs();
+        let height_rate = (height / self.height.amount.base - 1.0).abs();
+        let width = if width_rate >= height_rate {
+            width
+        } else {
+            height * ratio
+        };
+        let width_range = self.length_range(
+            &self.width,
+            &self.x_amount,
+            x_side,
+            element_manager.container_width,
+        );
+        let height_range = self.length_range(
+            &self.height,
+            &self.y_amount,
+            y_side,
+            element_manager.container_height,
+        );
+        let clamp = |length: f64, (min, max): (f64, f64)| length.min(max).max(min);
+        let height = clamp(clamp(width, width_range) / ratio, height_range);
+        let width = clamp(height * ratio, width_range);
+        let height = clamp(width / ratio, height_range);
+        self.width.amount.delta = width - self.width.amount.base;
+        self.height.amount.delta = height - self.height.amount.base;
+        if x_side == Some(HandleSide::Start) {
+            self.x_amount.delta = -self.width.amount.delta;
+        }
+        if y_side == Some(HandleSide::Start) {
+            self.y_amount.delta = -self.height.amount.delta;
         }
     }
 
@@ -106,33 +395,59 @@ impl BaseRect {
         self.y_amount.update_base();
         self.width.fix();
         self.height.fix();
+        self.grabbed_handle = None;
     }
 
     pub(crate) fn adjust(&mut self, element_manager: &mut ElementManager) {
-        let element = &element_manager.elements[self.element_index];
-        element
-            .set_attribute("width", self.width_value().to_string().as_str())
-            .unwrap();
-        element
-            .set_attribute("height", self.height_value().to_string().as_str())
-            .unwrap();
-    }
-    pub(crate) fn initial_adjust(&self, element_manager: &ElementManager) {
-        let element = &element_manager.elements[self.element_index];
+        element_manager.set_attribute(
+            self.element_index,
+            "width",
+            self.width_value().to_string().as_str(),
+        );
+        element_manager.set_attribute(
+            self.element_index,
+            "height",
+            self.height_value().to_string().as_str(),
+        );
+        self.adjust_resize_handles(element_manager);
+    }
+    fn adjust_resize_handles(&self, element_manager: &mut ElementManager) {
+        let mut handle_element_indexes = self.handle_element_indexes.iter();
+        for handle in ResizeHandle::ALL {
+            for (x, y, width, height) in self.handle_rects(handle) {
+                let Some((_, element_index)) = handle_element_indexes.next() else {
+                    return;
+                };
+                for (name, value) in [("x", x), ("y", y), ("width", width), ("height", height)] {
+                    element_manager.set_attribute(*element_index, name, value.to_string().as_str());
+                }
+            }
+        }
+    }
+    pub(crate) fn initial_adjust(&self, element_manager: &mut ElementManager) {
         if !self.color.is_empty() {
-            element.set_attribute("fill", self.color.as_str()).unwrap();
-        }
-        element
-            .set_attribute("x", self.x_value().to_string().as_str())
-            .unwrap();
-        element
-            .set_attribute("y", self.y_value().to_string().as_str())
-            .unwrap();
-        element
-            .set_attribute("width", self.width_value().to_string().as_str())
-            .unwrap();
-        element
-            .set_attribute("height", self.height_value().to_string().as_str())
-            .unwrap();
+            element_manager.set_attribute(self.element_index, "fill", self.color.as_str());
+        }
+        element_manager.set_attribute(self.element_index, "x", self.x_value().to_string().as_str());
+        element_manager.set_attribute(self.element_index, "y", self.y_value().to_string().as_str());
+        element_manager.set_attribute(
+            self.element_index,
+            "width",
+            self.width_value().to_string().as_str(),
+        );
+        element_manager.set_attribute(
+            self.element_index,
+            "height",
+            self.height_value().to_string().as_str(),
+        );
+        for (handle, element_index) in self.handle_element_indexes.iter() {
+            element_manager.set_attribute(*element_index, "fill", "transparent");
+            element_manager.set_attribute(
+                *element_index,
+                "style",
+                format!("cursor: {};", handle.cursor()).as_str(),
+            );
+        }
+        self.adjust_resize_handles(element_manager);
     }
 }
@@ -0,0 +1,140 @@
+use crate::binder::element_manager::ElementManager;
+use crate::figure::base_rect::ResizeHandle;
+use crate::figure::part_rect::{ButtonType, PartRect};
+use crate::figure::{Figure, PartType};
+
+// マウスポインターが乗っている Figure 内の場所
+// Part と Internal は parts と internal_part_rect の index
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum HoverTarget {
+    Frame,
+    Resize(ResizeHandle),
+    Part(usize),
+    Internal(usize, usize),
+}
+
+impl Figure {
+    // grab と同じ優先順位で探す
+    pub(crate) fn hover_target(&self, x: f64, y: f64) -> Option<HoverTarget> {
+        if !self.is_inner(x, y) {
+            return None;
+        }
+        if let Some(handle) = self.base_rect.resize_handle(
+            x - self.base_rect.x_amount.value(),
+            y - self.base_rect.y_amount.value(),
+        ) {
+            return Some(HoverTarget::Resize(handle));
+        }
+        let Some((index, parts)) = self
+            .parts
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, parts)| parts.is_inner(x, y, &self.base_rect))
+        else {
+            return Some(HoverTarget::Frame);
+        };
+        if let PartType::Scrollable = parts.part_type {
+            if let Some((internal_index, _)) = parts
+                .internal_part_rect
+                .iter()
+                .enumerate()
+                .rev()
+                .find(|(_, internal)| {
+                    matches!(
+                        internal.part_type,
+                        PartType::ScrollBarX(..) | PartType::ScrollBarY(..)
+                    ) && internal.is_inner(x, y, &self.base_rect)
+                })
+            {
+                return Some(HoverTarget::Internal(index, internal_index));
+            }
+        }
+        Some(HoverTarget::Part(index))
+    }
+    fn hover_part(&self, target: HoverTarget) -> Option<&PartRect> {
+        match target {
+            HoverTarget::Part(index) => self.parts.get(index),
+            HoverTarget::Internal(index, internal_index) => self
+                .parts
+                .get(index)
+                .and_then(|parts| parts.internal_part_rect.get(internal_index)),
+            HoverTarget::Frame | HoverTarget::Resize(..) => None,
+        }
+    }
+    // enter / leave のコールバックに渡す名前
+    pub(crate) fn hover_name(&self, target: HoverTarget) -> String {
+        if let HoverTarget::Resize(handle) = target {
+            return format!("resize-{}", handle.name());
+        }
+        let Some(parts) = self.hover_part(target) else {
+            return "frame".to_string();
+        };
+        match &parts.part_type {
+            PartType::Button(ButtonType::Minimize(..)) => "minimize-button",
+            PartType::Button(ButtonType::Maximize(..)) => "maximize-button",
+            PartType::Button(ButtonType::ShowContent(..)) => "show-content-button",
+            PartType::Button(ButtonType::Close) => "close-button",
+            PartType::Button(ButtonType::JumpToEnd(..)) => "jump-to-end-button",
+            PartType::Title(..) => "title",
+            PartType::Scrollable => "scrollable",
+            PartType::ScrollBarX(..) => "scroll-bar-x",
+            PartType::ScrollBarY(..) => "scroll-bar-y",
+            _ => "frame",
+        }
+        .to_string()
+    }
+    // container に設定するカーソル
+    pub(crate) fn hover_cursor(&self, target: HoverTarget) -> &'static str {
+        if let HoverTarget::Resize(handle) = target {
+            return handle.cursor();
+        }
+        let Some(parts) = self.hover_part(target) else {
+            return "move";
+        };
+        match &parts.part_type {
+            PartType::Button(..) => "pointer",
+            PartType::Title(..) => "grab",
+            _ => "default",
+        }
+    }
+    // hover クラスを付ける要素。ボタンは symbol を複製した g にも付ける
+    pub(crate) fn set_hover_style(
+        &self,
+        target: HoverTarget,
+        is_hovered: bool,
+        element_manager: &mut ElementManager,
+    ) {
+        let Some(parts) = self.hover_part(target) else {
+            return;
+        };
+        let element = element_manager.element(parts.element_index);
+        let mut elements = vec![element];
+        if let PartType::Button(..) = parts.part_type {
+            if let Some(group) = element_manager.get_sibling_group(parts.element_index) {
+                elements.push(group);
+            }
+        }
+        for element in elements {
+            // テンプレートに書いたクラスは残し、hover だけを付け外しする
+            let class = element_manager
+                .backend
+                .get_attribute(element, "class")
+                .unwrap_or_default();
+            let mut classes: Vec<&str> = class
+                .split_whitespace()
+                .filter(|name| *name != "hover")
+                .collect();
+            if is_hovered {
+                classes.push("hover");
+            }
+            if classes.is_empty() {
+                element_manager.backend.remove_attribute(element, "class");
+            } else {
+                element_manager
+                    .backend
+                    .set_attribute(element, "class", classes.join(" ").as_str());
+            }
+        }
+    }
+}
@@ -2,9 +2,12 @@ use crate::binder::element_manager::ElementManager;
 use crate::binder::ContentManager;
 use crate::figure::base_rect::BaseRect;
 use crate::figure::AmountPositionType::{ContentBase, End, Ignore, Start};
-use crate::figure::{AmountPositionType, Figure, PartType, ScrollBarState, TitleState};
+use crate::figure::{
+    AmountPositionType, Figure, FollowTail, Geometry, PartType, ScrollBarState, TitleState,
+    WindowState,
+};
 use crate::math::Amount;
-use web_sys::Element;
+use crate::render_backend::NodeId;
 
 pub(crate) struct PartRect {
     pub(crate) x_amounts: Vec<(f64, AmountPositionType)>,
@@ -20,6 +23,7 @@ pub(crate) struct PartRect {
 }
 
 impl PartRect {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn default_scrollable(
         margin: f64,
         offset_x: f64,
@@ -28,7 +32,7 @@ impl PartRect {
         color: &str,
         content_part_type: PartType,
         element_manager: &mut ElementManager,
-        group_element: &Element,
+        group_element: NodeId,
     ) -> PartRect {
         PartRect {
             x_amounts: vec![(margin + offset_x, Start), (-margin, End)],
@@ -54,7 +58,7 @@ impl PartRect {
                     PartType::ScrollBarX(ScrollBarState::new()),
                     "",
                     element_manager
-                        .create_element_with_defs_id(&group_element, "def-default-scroll-bar-x"),
+                        .create_element_with_defs_id(group_element, "def-default-scroll-bar-x"),
                 ),
                 PartRect::default_scroll_bar_xy(
                     thickness,
@@ -63,7 +67,7 @@ impl PartRect {
                     PartType::ScrollBarY(ScrollBarState::new()),
                     "",
                     element_manager
-                        .create_element_with_defs_id(&group_element, "def-default-scroll-bar-y"),
+                        .create_element_with_defs_id(group_element, "def-default-scroll-bar-y"),
                 ),
             ],
             is_initialized: false,
@@ -163,11 +167,24 @@ impl PartRect {
                 element_index,
                 "def-default-minimize-button",
             ),
+            ButtonType::Maximize(..) => ButtonType::draw_button(
+                element_manager,
+                element_index,
+                "def-default-maximize-button",
+            ),
             ButtonType::ShowContent(..) => ButtonType::draw_button(
                 element_manager,
                 element_index,
                 "def-default-show-content-button",
             ),
+            ButtonType::Close => {
+                ButtonType::draw_button(element_manager, element_index, "def-default-close-button")
+            }
+            ButtonType::JumpToEnd(..) => ButtonType::draw_button(
+                element_manager,
+                element_index,
+                "def-default-jump-to-end-button",
+            ),
         }
         PartRect {
             x_amounts: vec![x_amount.clone(), (x_amount.0 + size, x_amount.1)],
@@ -207,7 +224,8 @@ impl PartRect {
             // Scrollable の internal_pert_rect でのみ grab できる
             | PartType::ScrollBarX(..)
             | PartType::ScrollBarY(..)
-            | PartType::TableContent(..) => false,
+            | PartType::TableContent(..)
+            | PartType::LogContent(..) => false,
             PartType::Scrollable => {
                 if let Some(internal) =
                     self.internal_part_rect
@@ -226,6 +244,18 @@ impl PartRect {
                     internal.is_grabbed = true;
                     true
                 } else {
+                    // 溝を押した時は grab せず、ボタンと同じく push として扱ってページ送りする
+                    for internal in self.internal_part_rect.iter_mut() {
+                        let Some(position) = internal.track_position(x, y, base_rect) else {
+                            continue;
+                        };
+                        if let PartType::ScrollBarX(scroll_bar_state)
+                        | PartType::ScrollBarY(scroll_bar_state) = &mut internal.part_type
+                        {
+                            self.is_pushed = scroll_bar_state.start_paging(position);
+                        }
+                        break;
+                    }
                     // Scrollable 内の特定の要素でなければ grab させない
                     false
                 }
@@ -240,80 +270,116 @@ impl PartRect {
         };
         self.is_grabbed
     }
-    pub(crate) fn adjust(
-        &mut self,
-        base_rect: &BaseRect,
-        element_manager: &mut ElementManager,
-        content_manager: &ContentManager,
-    ) {
-        let element = &element_manager.elements[self.element_index];
+    pub(crate) fn adjust(&mut self, base_rect: &BaseRect, element_manager: &mut ElementManager) {
         if !self.is_initialized {
             if !self.color.is_empty() {
-                element.set_attribute("fill", self.color.as_str()).unwrap();
+                element_manager.set_attribute(self.element_index, "fill", self.color.as_str());
             }
             if let PartType::Title(title_status) = &self.part_type {
-                element_manager.elements[self.element_index]
-                    .next_element_sibling()
-                    .unwrap()
-                    .set_inner_html(title_status.title.as_str());
+                let title_element = element_manager
+                    .backend
+                    .next_sibling(element_manager.element(self.element_index))
+                    .unwrap();
+                element_manager
+                    .backend
+                    .set_text(title_element, title_status.title.as_str());
             }
             self.is_initialized = true;
         }
         let x_value = self.x_value(base_rect).to_string();
         let y_value = self.y_value(base_rect).to_string();
-        element.set_attribute("x", x_value.as_str()).unwrap();
-        element.set_attribute("y", y_value.as_str()).unwrap();
-        element
-            .set_attribute("width", self.width_value(base_rect).to_string().as_str())
-            .unwrap();
+        element_manager.set_attribute(self.element_index, "x", x_value.as_str());
+        element_manager.set_attribute(self.element_index, "y", y_value.as_str());
+        element_manager.set_attribute(
+            self.element_index,
+            "width",
+            self.width_value(base_rect).to_string().as_str(),
+        );
         // TODO
         // 最小化で0.0チェックを追加したが違和感
         let height = self.height_value(base_rect);
         if height > 0.0 {
-            element
-                .set_attribute("height", height.to_string().as_str())
-                .unwrap();
+            element_manager.set_attribute(
+                self.element_index,
+                "height",
+                height.to_string().as_str(),
+            );
         }
         if self.has_content {
-            if let Some(content_element) =
-                element_manager.elements[self.element_index].next_element_sibling()
-            {
-                if content_element.tag_name() == "g" {
-                    content_element
-                        .set_attribute(
-                            "transform",
-                            format!("translate({}, {})", x_value, y_value).as_str(),
-                        )
-                        .unwrap();
-                }
+            if let Some(content_element) = element_manager.get_sibling_group(self.element_index) {
+                element_manager.backend.set_attribute(
+                    content_element,
+                    "transform",
+                    format!("translate({}, {})", x_value, y_value).as_str(),
+                );
             }
         }
         for internal in self.internal_part_rect.iter_mut() {
-            internal.adjust(base_rect, element_manager, content_manager);
+            internal.adjust(base_rect, element_manager);
         }
         if let PartType::Scrollable = self.part_type {
-            self.adjust_scrollable(base_rect, element_manager, content_manager);
+            self.adjust_scrollable(base_rect, element_manager);
         }
     }
 
-    fn hide(&self, element_manager: &ElementManager) {
-        let element = &element_manager.elements[self.element_index];
-        element.set_attribute("width", "0").unwrap();
+    fn hide(&self, element_manager: &mut ElementManager) {
+        element_manager.set_attribute(self.element_index, "width", "0");
     }
 
-    fn adjust_scrollable(
-        &self,
-        base_rect: &BaseRect,
-        element_manager: &ElementManager,
-        content_manager: &ContentManager,
-    ) {
+    fn adjust_scrollable(&mut self, base_rect: &BaseRect, element_manager: &mut ElementManager) {
+        // 内容を先に描き直してから大きさを測り、スクロールバーをそれに合わせる
+        let mut clip_rects = vec![];
+        let mut row_count = 0;
+        // ログの古い行を捨てて内容が上にずれた高さ
+        let mut removed_height = 0.0;
+        for internal in self.internal_part_rect.iter_mut() {
+            if let PartType::LogContent(log_content_state) = &mut internal.part_type {
+                let sibling_group = element_manager
+                    .get_sibling_group(internal.element_index)
+                    .unwrap();
+                let (clip_rect, removed_count) =
+                    log_content_state.render(element_manager, sibling_group);
+                clip_rects.push(clip_rect);
+                row_count += log_content_state.total_count();
+                removed_height += log_content_state.line_height() * removed_count as f64;
+            } else if let PartType::TableContent(table_content_state) = &internal.part_type {
+                // 内容は Binder::update の refresh_content で取得済み
+                row_count += table_content_state.row_count();
+            }
+        }
+        // 表は行数から高さが決まるので、先にスクロール位置を求めて表示領域に入る行だけを描く
+        self.update_scroll_bars(base_rect, element_manager);
+        let offset_y = self
+            .scroll_bar_y()
+            .map_or(0.0, |scroll_bar_state| scroll_bar_state.scroll_position());
+        let viewport_width = self.width_value(base_rect);
+        let viewport_height = self.height_value(base_rect);
+        for internal in self.internal_part_rect.iter_mut() {
+            if let PartType::TableContent(table_content_state) = &mut internal.part_type {
+                let sibling_group = element_manager
+                    .get_sibling_group(internal.element_index)
+                    .unwrap();
+                let rows = table_content_state.visible_rows(offset_y, viewport_height);
+                clip_rects.push(table_content_state.render(element_manager, sibling_group, rows));
+                table_content_state.layout_columns(element_manager, viewport_width);
+            }
+        }
+        // 幅は描いた行から測る
+        self.update_scroll_bars(base_rect, element_manager);
+        if let Some(scroll_bar_state) = self.scroll_bar_y_mut() {
+            // 遡って読んでいる時は、捨てた行の分だけ戻して同じ行を表示し続ける
+            if removed_height > 0.0 && !scroll_bar_state.is_at_end() {
+                scroll_bar_state.scroll_by(-removed_height);
+            }
+            scroll_bar_state.update_row_count(row_count);
+        }
         let (has_content, group_x, group_y, content_element_index) =
             self.get_internal_content_size(element_manager);
         if !has_content {
             return;
         }
-        let internal_max_width = group_x / element_manager.scale + 10.0;
-        let internal_max_height = group_y / element_manager.scale + 10.0;
+        let internal_max_width = group_x + 10.0;
+        let internal_max_height = group_y + 10.0;
         // TODO
         // 2値のハードコーディングをやめる
         let scroll_bar_x_height = 10.0;
@@ -352,11 +418,16 @@ impl PartRect {
                     let offset_max_width = internal_max_width - base_width;
                     let offset_x =
                         scroll_bar_state.start_amount.value() / max_delta * offset_max_width;
-                    let content = &element_manager.elements[content_element_index];
-                    let content_x: f64 = content.get_attribute("x").unwrap().parse().unwrap();
-                    content
-                        .set_attribute("x", (content_x - offset_x).to_string().as_str())
+                    let content_x: f64 = element_manager
+                        .get_attribute(content_element_index, "x")
+                        .unwrap()
+                        .parse()
                         .unwrap();
+                    element_manager.set_attribute(
+                        content_element_index,
+                        "x",
+                        (content_x - offset_x).to_string().as_str(),
+                    );
                     table_content_x -= offset_x;
                 }
             }
@@ -372,108 +443,377 @@ impl PartRect {
         } else {
             for internal in self.internal_part_rect.iter() {
                 if let PartType::ScrollBarY(scroll_bar_state) = &internal.part_type {
-                    let using_height_ratio = if width_ratio > threshold {
-                        height_ratio_with_bar
-                    } else {
-                        height_ratio
-                    };
-                    // let bar_height = self.height_value(base_rect) / using_height_ratio;
                     let bar_height = scroll_bar_state.length;
                     let max_delta = base_height - bar_height;
                     let offset_max_height = internal_max_height - base_height;
                     let offset_y =
                         scroll_bar_state.start_amount.value() / max_delta * offset_max_height;
-                    let content = &element_manager.elements[content_element_index];
-                    let content_y: f64 = content.get_attribute("y").unwrap().parse().unwrap();
-                    content
-                        .set_attribute("y", (content_y - offset_y).to_string().as_str())
+                    let content_y: f64 = element_manager
+                        .get_attribute(content_element_index, "y")
+                        .unwrap()
+                        .parse()
                         .unwrap();
+                    element_manager.set_attribute(
+                        content_element_index,
+                        "y",
+                        (content_y - offset_y).to_string().as_str(),
+                    );
                     table_content_y -= offset_y;
                 }
             }
         }
-        for internal in self.internal_part_rect.iter() {
-            if let PartType::TableContent(table_content_state) = &internal.part_type {
-                let sibling_group = element_manager.elements[internal.element_index]
-                    .next_element_sibling()
+        for (internal, clip_rect) in self
+            .internal_part_rect
+            .iter()
+            .filter(|internal| internal.is_content())
+            .zip(clip_rects)
+        {
+            if let Some(content_id_token) = internal.content_id_token() {
+                let sibling_group = element_manager
+                    .get_sibling_group(internal.element_index)
                     .unwrap();
                 // TODO
                 // 最小化対応で height >= 0.0 チェックを追加したが違和感
-                sibling_group.set_inner_html(format!("<clipPath id='clip-path-table-content-{}'><rect fill='white' x='{}' y='{}' width='{}' height='{}'></rect></clipPath>", table_content_state.content_id_token, -table_content_x, -table_content_y, self.width_value(base_rect), self.height_value(base_rect).max(0.0)).as_str());
-                table_content_state.init(element_manager, &sibling_group, content_manager);
+                for (name, value) in [
+                    ("fill", "white".to_string()),
+                    ("x", (-table_content_x).to_string()),
+                    ("y", (-table_content_y).to_string()),
+                    ("width", self.width_value(base_rect).to_string()),
+                    ("height", self.height_value(base_rect).max(0.0).to_string()),
+                ] {
+                    element_manager
+                        .backend
+                        .set_attribute(clip_rect, name, value.as_str());
+                }
+                if let PartType::TableContent(table_content_state) = &internal.part_type {
+                    table_content_state.pin_header(
+                        element_manager,
+                        -table_content_x,
+                        -table_content_y,
+                        self.width_value(base_rect),
+                    );
+                    table_content_state.stretch_row_backgrounds(
+                        element_manager,
+                        -table_content_x,
+                        self.width_value(base_rect),
+                    );
+                }
                 table_content_x += self.x_value(base_rect);
                 table_content_y += self.y_value(base_rect);
-                sibling_group
-                    .set_attribute(
-                        "transform",
-                        format!("translate({}, {})", table_content_x, table_content_y).as_str(),
-                    )
-                    .unwrap();
-                sibling_group
-                    .set_attribute(
-                        "clip-path",
-                        format!(
-                            "url(#clip-path-table-content-{})",
-                            table_content_state.content_id_token
-                        )
-                        .as_str(),
-                    )
-                    .unwrap();
+                element_manager.backend.set_attribute(
+                    sibling_group,
+                    "transform",
+                    format!("translate({}, {})", table_content_x, table_content_y).as_str(),
+                );
+                element_manager.backend.set_attribute(
+                    sibling_group,
+                    "clip-path",
+                    format!("url(#clip-path-table-content-{})", content_id_token).as_str(),
+                );
             }
         }
         // TODO
         // 最小化対応で height <= 0.0 の時に Scrollableを隠すようにしたが違和感
         if base_height <= 0.0 {
-            element_manager.elements[self.element_index]
-                .set_attribute("height", "0")
-                .unwrap();
+            element_manager.set_attribute(self.element_index, "height", "0");
         }
     }
     pub(crate) fn get_internal_content_size(
         &self,
         element_manager: &ElementManager,
     ) -> (bool, f64, f64, usize) {
-        let content_part = self.internal_part_rect.iter().find(|internal| {
-            if let PartType::TableContent(..) = internal.part_type {
-                true
-            } else {
-                false
-            }
-        });
-        if content_part.is_none() {
-            return (false, 0.0, 0.0, 0);
-        }
-        let found_content_part = content_part.unwrap();
+        let content_part = self
+            .internal_part_rect
+            .iter()
+            .find(|internal| internal.is_content());
+        let found_content_part = match content_part {
+            Some(found_content_part) => found_content_part,
+            None => return (false, 0.0, 0.0, 0),
+        };
         let content_index = found_content_part.element_index;
-        if let Some(sibling) = element_manager.elements[content_index].next_element_sibling() {
-            if sibling.tag_name() == "g" {
-                let bounding = sibling.get_bounding_client_rect();
-                return (true, bounding.width(), bounding.height(), content_index);
-            }
+        if let Some((width, height)) = found_content_part.content_size(element_manager) {
+            return (true, width, height, content_index);
         }
         (false, 0.0, 0.0, 0)
     }
 }
 
 impl PartRect {
+    pub(crate) fn collect_element_indexes(&self, element_indexes: &mut Vec<usize>) {
+        element_indexes.push(self.element_index);
+        for internal in self.internal_part_rect.iter() {
+            internal.collect_element_indexes(element_indexes);
+        }
+    }
+    // 内容と表示領域の大きさから、スクロールバーの長さと位置を計算し直す
+    pub(crate) fn update_scroll_bars(
+        &mut self,
+        base_rect: &BaseRect,
+        element_manager: &ElementManager,
+    ) {
+        let mut internal_max_width: f64 = 0.0;
+        let mut internal_max_height: f64 = 0.0;
+        let scrollable_width = self.width_value(base_rect);
+        let scrollable_height = self.height_value(base_rect);
+        for internal in self.internal_part_rect.iter() {
+            match internal.part_type {
+                PartType::ScrollBarX(..) | PartType::ScrollBarY(..) => {}
+                _ => {
+                    internal_max_width = internal_max_width.max(internal.width_value(base_rect));
+                    internal_max_height = internal_max_height.max(internal.height_value(base_rect));
+                    if let Some((width, height)) = internal.content_size(element_manager) {
+                        internal_max_width = internal_max_width.max(width + 10.0);
+                        internal_max_height = internal_max_height.max(height + 10.0);
+                    }
+                }
+            }
+        }
+        for internal in self.internal_part_rect.iter_mut() {
+            match &mut internal.part_type {
+                PartType::ScrollBarX(scroll_bar_state) => {
+                    scroll_bar_state.update(internal_max_width, scrollable_width);
+                }
+                PartType::ScrollBarY(scroll_bar_state) => {
+                    scroll_bar_state.update(internal_max_height, scrollable_height);
+                }
+                _ => {}
+            }
+        }
+    }
+    // 描画した内容の大きさ。表は描画していない行も含めた高さにする
+    fn content_size(&self, element_manager: &ElementManager) -> Option<(f64, f64)> {
+        let sibling = element_manager.get_sibling_group(self.element_index)?;
+        let (width, height) = element_manager.backend.bounding_size(sibling);
+        match &self.part_type {
+            PartType::TableContent(table_content_state) => {
+                Some((width, table_content_state.logical_height()))
+            }
+            _ => Some((width, height)),
+        }
+    }
+    // Scrollable の中に表示する内容
+    fn is_content(&self) -> bool {
+        matches!(
+            self.part_type,
+            PartType::TableContent(..) | PartType::LogContent(..)
+        )
+    }
+    fn content_id_token(&self) -> Option<&str> {
+        match &self.part_type {
+            PartType::TableContent(table_content_state) => {
+                Some(table_content_state.content_id_token.as_str())
+            }
+            PartType::LogContent(log_content_state) => {
+                Some(log_content_state.content_id_token.as_str())
+            }
+            _ => None,
+        }
+    }
+    // ログの内容なら行を追加する
+    pub(crate) fn push_line(&mut self, line: &str) -> bool {
+        match self.internal_part_rect.iter_mut().find_map(|internal| {
+            match &mut internal.part_type {
+                PartType::LogContent(log_content_state) => Some(log_content_state),
+                _ => None,
+            }
+        }) {
+            Some(log_content_state) => {
+                log_content_state.push_line(line);
+                true
+            }
+            None => false,
+        }
+    }
+    fn scroll_bar_y(&self) -> Option<&ScrollBarState> {
+        self.internal_part_rect
+            .iter()
+            .find_map(|internal| match &internal.part_type {
+                PartType::ScrollBarY(scroll_bar_state) => Some(scroll_bar_state),
+                _ => None,
+            })
+    }
+    fn scroll_bar_y_mut(&mut self) -> Option<&mut ScrollBarState> {
+        self.internal_part_rect
+            .iter_mut()
+            .find_map(|internal| match &mut internal.part_type {
+                PartType::ScrollBarY(scroll_bar_state) => Some(scroll_bar_state),
+                _ => None,
+            })
+    }
+    pub(crate) fn set_follow_tail(&mut self, follow_tail: bool) {
+        if let Some(scroll_bar_state) = self.scroll_bar_y_mut() {
+            scroll_bar_state.follow_tail = follow_tail.then(FollowTail::default);
+        }
+    }
+    pub(crate) fn content_key(&self) -> Option<&str> {
+        self.internal_part_rect
+            .iter()
+            .find_map(|internal| match &internal.part_type {
+                PartType::TableContent(table_content_state) => {
+                    Some(table_content_state.content_key())
+                }
+                _ => None,
+            })
+    }
+    // 末尾に追従していない時は None
+    pub(crate) fn unread_count(&self) -> Option<usize> {
+        self.internal_part_rect
+            .iter()
+            .find_map(|internal| match &internal.part_type {
+                PartType::ScrollBarY(scroll_bar_state) => scroll_bar_state
+                    .follow_tail
+                    .as_ref()
+                    .map(|follow_tail| follow_tail.unread_count),
+                _ => None,
+            })
+    }
+    pub(crate) fn stop_paging(&mut self) {
+        for internal in self.internal_part_rect.iter_mut() {
+            if let PartType::ScrollBarX(scroll_bar_state) | PartType::ScrollBarY(scroll_bar_state) =
+                &mut internal.part_type
+            {
+                scroll_bar_state.paging = None;
+            }
+        }
+    }
+    pub(crate) fn refresh_content(
+        &mut self,
+        element_manager: &mut ElementManager,
+        content_manager: &ContentManager,
+    ) -> bool {
+        let mut is_changed = false;
+        for internal in self.internal_part_rect.iter_mut() {
+            if let PartType::TableContent(table_content_state) = &mut internal.part_type {
+                is_changed |= table_content_state.refresh(element_manager, content_manager);
+            }
+        }
+        is_changed
+    }
+    pub(crate) fn take_template_errors(&mut self) -> Vec<String> {
+        self.internal_part_rect
+            .iter_mut()
+            .flat_map(|internal| match &mut internal.part_type {
+                PartType::TableContent(table_content_state) => {
+                    table_content_state.take_template_errors()
+                }
+                _ => vec![],
+            })
+            .collect()
+    }
+    pub(crate) fn repeat_paging(&mut self) -> bool {
+        let mut is_scrolled = false;
+        for internal in self.internal_part_rect.iter_mut() {
+            if let PartType::ScrollBarX(scroll_bar_state) | PartType::ScrollBarY(scroll_bar_state) =
+                &mut internal.part_type
+            {
+                is_scrolled |= scroll_bar_state.repeat_paging();
+            }
+        }
+        is_scrolled
+    }
+    // スクロールバーの溝の上なら、溝の先頭からの位置を返す
+    fn track_position(&self, raw_x: f64, raw_y: f64, base_rect: &BaseRect) -> Option<f64> {
+        let x = raw_x - base_rect.x_amount.value();
+        let y = raw_y - base_rect.y_amount.value();
+        let (scroll_bar_state, main, cross, thumb_start, cross_start, cross_length) =
+            match &self.part_type {
+                PartType::ScrollBarX(scroll_bar_state) => (
+                    scroll_bar_state,
+                    x,
+                    y,
+                    self.x_value(base_rect),
+                    self.y_value(base_rect),
+                    self.height_value(base_rect),
+                ),
+                PartType::ScrollBarY(scroll_bar_state) => (
+                    scroll_bar_state,
+                    y,
+                    x,
+                    self.y_value(base_rect),
+                    self.x_value(base_rect),
+                    self.width_value(base_rect),
+                ),
+                _ => return None,
+            };
+        // はみ出していない時はスクロールバー自体が表示されない
+        if scroll_bar_state.length <= 0.0
+            || cross < cross_start
+            || cross > cross_start + cross_length
+        {
+            return None;
+        }
+        let position = main - (thumb_start - scroll_bar_state.start_amount.value());
+        if position < 0.0 || position > scroll_bar_state.scrollable_length {
+            return None;
+        }
+        Some(position)
+    }
     pub(crate) fn is_inner(&self, raw_x: f64, raw_y: f64, base_rect: &BaseRect) -> bool {
+        if self.is_hidden() {
+            return false;
+        }
         let x = raw_x - base_rect.x_amount.value();
         let y = raw_y - base_rect.y_amount.value();
         let x_value = self.x_value(base_rect);
-        if x_value > x {
-            return false;
-        } else if x_value + self.width_value(base_rect) < x {
+        if x_value > x || x_value + self.width_value(base_rect) < x {
             return false;
         }
         let y_value = self.y_value(base_rect);
-        if y_value > y {
-            return false;
-        } else if y_value + self.height_value(base_rect) < y {
+        if y_value > y || y_value + self.height_value(base_rect) < y {
             return false;
         }
         true
     }
 
+    // 未読がない時の末尾へ移動するボタン
+    fn is_hidden(&self) -> bool {
+        matches!(
+            &self.part_type,
+            PartType::Button(ButtonType::JumpToEnd(jump_to_end_option))
+                if jump_to_end_option.unread_count == 0
+        )
+    }
+    // adjust の後に呼ぶ。未読の行数をボタンの左に表示し、未読がなければボタンごと隠す
+    pub(crate) fn adjust_jump_to_end(
+        &mut self,
+        unread_count: usize,
+        base_rect: &BaseRect,
+        element_manager: &mut ElementManager,
+    ) {
+        let x_value = self.x_value(base_rect);
+        let y_value = self.y_value(base_rect);
+        let PartType::Button(ButtonType::JumpToEnd(jump_to_end_option)) = &mut self.part_type
+        else {
+            return;
+        };
+        jump_to_end_option.unread_count = unread_count;
+        let count_text = jump_to_end_option.count_text;
+        let button_group = element_manager
+            .get_sibling_group(self.element_index)
+            .unwrap();
+        if unread_count == 0 {
+            self.hide(element_manager);
+            element_manager
+                .backend
+                .set_attribute(button_group, "display", "none");
+            element_manager.backend.set_text(count_text, "");
+            return;
+        }
+        element_manager
+            .backend
+            .remove_attribute(button_group, "display");
+        element_manager
+            .backend
+            .set_text(count_text, unread_count.to_string().as_str());
+        for (name, value) in [
+            ("x", (x_value - 4.0).to_string()),
+            ("y", (y_value + 15.0).to_string()),
+        ] {
+            element_manager
+                .backend
+                .set_attribute(count_text, name, value.as_str());
+        }
+    }
     fn x_value(&self, base_rect: &BaseRect) -> f64 {
         let (ref amount, ref amount_position_type) = self.x_amounts[0];
         let mut amount = match amount_position_type {
@@ -481,11 +821,8 @@ impl PartRect {
             End => base_rect.x_value() + base_rect.width_value() + amount,
             Ignore => 0.0,
         };
-        match &self.part_type {
-            PartType::ScrollBarX(scroll_bar_state) => {
-                amount += scroll_bar_state.start_amount.value()
-            }
-            _ => {}
+        if let PartType::ScrollBarX(scroll_bar_state) = &self.part_type {
+            amount += scroll_bar_state.start_amount.value()
         }
         amount
     }
@@ -496,11 +833,8 @@ impl PartRect {
             End => base_rect.y_value() + base_rect.height_value() + amount,
             Ignore => 0.0,
         };
-        match &self.part_type {
-            PartType::ScrollBarY(scroll_bar_state) => {
-                amount += scroll_bar_state.start_amount.value()
-            }
-            _ => {}
+        if let PartType::ScrollBarY(scroll_bar_state) = &self.part_type {
+            amount += scroll_bar_state.start_amount.value()
         }
         amount
     }
@@ -537,9 +871,37 @@ pub(crate) struct MinimizeOption {
 }
 
 impl MinimizeOption {
+    // 最小化している時に押されたら元の大きさに戻す
     pub(crate) fn minimize_window(&self, figure: &mut Figure) {
-        figure.base_rect.height.amount.base = self.minimized_height;
-        figure.base_rect.width.amount.base = self.minimized_width;
+        if figure.window_state == WindowState::Minimized {
+            figure.restore_size();
+            return;
+        }
+        figure.save_geometry();
+        figure.base_rect.height.amount = Amount::new(self.minimized_height);
+        figure.base_rect.width.amount = Amount::new(self.minimized_width);
+        figure.window_state = WindowState::Minimized;
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct MaximizeOption {}
+
+impl MaximizeOption {
+    // container 全体に広げる。最大化している時に押されたら元の位置と大きさに戻す
+    pub(crate) fn maximize_window(&self, figure: &mut Figure, element_manager: &ElementManager) {
+        if figure.window_state == WindowState::Maximized {
+            figure.restore_geometry();
+            return;
+        }
+        figure.save_geometry();
+        figure.set_geometry(&Geometry {
+            x: 0.0,
+            y: 0.0,
+            width: element_manager.container_width,
+            height: element_manager.container_height,
+        });
+        figure.window_state = WindowState::Maximized;
     }
 }
 
@@ -554,12 +916,12 @@ impl ShowContentOption {
     ) {
         let mut content_width = 0.0;
         let mut content_height = 0.0;
-        if let Some(_) = figure.parts.iter().find(|parts| {
+        if figure.parts.iter().any(|parts| {
             if let PartType::Scrollable = parts.part_type {
                 let (content_flag, width, height, ..) =
                     parts.get_internal_content_size(element_manager);
-                content_width = width / element_manager.scale;
-                content_height = height / element_manager.scale;
+                content_width = width;
+                content_height = height;
                 content_flag
             } else {
                 false
@@ -573,24 +935,28 @@ impl ShowContentOption {
     }
 }
 
+#[derive(Clone, Debug)]
+pub(crate) struct JumpToEndOption {
+    // ボタンの左に未読の行数を表示する text 要素
+    pub(crate) count_text: NodeId,
+    pub(crate) unread_count: usize,
+}
+
 #[derive(Clone, Debug)]
 pub enum ButtonType {
     Minimize(MinimizeOption),
+    Maximize(MaximizeOption),
     ShowContent(ShowContentOption),
+    Close,
+    // 末尾に追従する Scrollable で、遡っている間だけ表示する
+    JumpToEnd(JumpToEndOption),
 }
 
 impl ButtonType {
     fn draw_button(element_manager: &mut ElementManager, element_index: usize, symbol_id: &str) {
-        let content_group = element_manager.elements[element_index]
-            .next_element_sibling()
-            .unwrap();
-        let symbol = element_manager
-            .document
-            .get_element_by_id(symbol_id)
-            .unwrap();
-        for n in 0..symbol.child_nodes().length() {
-            let node = symbol.child_nodes().item(n).unwrap().clone_node().unwrap();
-            content_group.append_child(&node).unwrap();
-        }
+        let content_group = element_manager.get_sibling_group(element_index).unwrap();
+        element_manager
+            .backend
+            .clone_symbol_children(content_group, symbol_id);
     }
 }
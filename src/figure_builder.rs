@@ -0,0 +1,293 @@
+use crate::binder::element_manager::ElementManager;
+use crate::content::{ColumnStyle, ColumnWidth, LogContentState, TableContentState};
+use crate::figure::part_rect::{ButtonType, MaximizeOption, MinimizeOption, ShowContentOption};
+use crate::figure::{Figure, PartType, RectLength};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+pub use crate::content::TextAnchorType;
+
+// Scrollable の中に表示する内容の種類
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContentKind {
+    Empty,
+    Table,
+    Log,
+    // Binder::push_line で行を追加していくログ
+    AppendLog,
+}
+
+// ウィンドウを container の内側に留める方法
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Containment {
+    // ウィンドウ全体を container の内側に置く
+    Inside,
+    // タイトルバーをつかめる範囲だけ残して、はみ出すことを許す
+    TitleBarVisible,
+    Unconstrained,
+}
+
+// Binder::add_figure に渡すウィンドウの定義
+// JS からは new FigureBuilder().title("...").position(100, 100) のようにつなげて使う
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct FigureBuilder {
+    title: String,
+    x: f64,
+    y: f64,
+    width: (f64, f64, f64),
+    height: (f64, f64, f64),
+    frame_color: String,
+    margin: f64,
+    title_height: f64,
+    has_minimize_button: bool,
+    has_maximize_button: bool,
+    has_show_content_button: bool,
+    has_close_button: bool,
+    aspect_ratio: Option<f64>,
+    containment: Containment,
+    resize_handle_thickness: Option<f64>,
+    content_kind: ContentKind,
+    content_token: String,
+    content_key: String,
+    columns: Vec<ColumnStyle>,
+    follow_tail: bool,
+    max_lines: usize,
+}
+
+impl Default for FigureBuilder {
+    fn default() -> FigureBuilder {
+        FigureBuilder::new()
+    }
+}
+
+#[wasm_bindgen]
+impl FigureBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> FigureBuilder {
+        FigureBuilder {
+            title: "".to_string(),
+            x: 100.0,
+            y: 100.0,
+            width: (200.0, 180.0, f64::INFINITY),
+            height: (300.0, 30.0, f64::INFINITY),
+            frame_color: "#333".to_string(),
+            margin: 5.0,
+            title_height: 25.0,
+            has_minimize_button: true,
+            has_maximize_button: false,
+            has_show_content_button: true,
+            has_close_button: false,
+            aspect_ratio: None,
+            containment: Containment::Inside,
+            resize_handle_thickness: None,
+            content_kind: ContentKind::Table,
+            content_token: "".to_string(),
+            content_key: "".to_string(),
+            columns: vec![],
+            follow_tail: false,
+            max_lines: 1000,
+        }
+    }
+    pub fn title(mut self, title: &str) -> FigureBuilder {
+        self.title = title.to_string();
+        self
+    }
+    pub fn position(mut self, x: f64, y: f64) -> FigureBuilder {
+        self.x = x;
+        self.y = y;
+        self
+    }
+    // max に Infinity を渡すと上限なし
+    pub fn width(mut self, width: f64, min: f64, max: f64) -> FigureBuilder {
+        self.width = (width, min, max);
+        self
+    }
+    pub fn height(mut self, height: f64, min: f64, max: f64) -> FigureBuilder {
+        self.height = (height, min, max);
+        self
+    }
+    // width / height の比率を保ってサイズ変更する。0 以下を渡すと解除
+    pub fn aspect_ratio(mut self, aspect_ratio: f64) -> FigureBuilder {
+        self.aspect_ratio = if aspect_ratio > 0.0 {
+            Some(aspect_ratio)
+        } else {
+            None
+        };
+        self
+    }
+    pub fn containment(mut self, containment: Containment) -> FigureBuilder {
+        self.containment = containment;
+        self
+    }
+    // 省略した場合は margin と同じ太さ
+    pub fn resize_handle_thickness(mut self, thickness: f64) -> FigureBuilder {
+        self.resize_handle_thickness = Some(thickness);
+        self
+    }
+    pub fn frame_color(mut self, frame_color: &str) -> FigureBuilder {
+        self.frame_color = frame_color.to_string();
+        self
+    }
+    pub fn margin(mut self, margin: f64) -> FigureBuilder {
+        self.margin = margin;
+        self
+    }
+    pub fn title_height(mut self, title_height: f64) -> FigureBuilder {
+        self.title_height = title_height;
+        self
+    }
+    pub fn minimize_button(mut self, visible: bool) -> FigureBuilder {
+        self.has_minimize_button = visible;
+        self
+    }
+    pub fn maximize_button(mut self, visible: bool) -> FigureBuilder {
+        self.has_maximize_button = visible;
+        self
+    }
+    pub fn show_content_button(mut self, visible: bool) -> FigureBuilder {
+        self.has_show_content_button = visible;
+        self
+    }
+    pub fn close_button(mut self, visible: bool) -> FigureBuilder {
+        self.has_close_button = visible;
+        self
+    }
+    pub fn content_kind(mut self, content_kind: ContentKind) -> FigureBuilder {
+        self.content_kind = content_kind;
+        self
+    }
+    // 行が増えた時に末尾を表示し続ける。ログ向け
+    pub fn follow_tail(mut self, follow_tail: bool) -> FigureBuilder {
+        self.follow_tail = follow_tail;
+        self
+    }
+    // ContentKind::AppendLog で残す行数。超えたら古い行から捨てる
+    pub fn max_lines(mut self, max_lines: usize) -> FigureBuilder {
+        self.max_lines = max_lines;
+        self
+    }
+    // clipPath の id に使う文字列
+    // 省略した場合は Figure の id から生成する
+    pub fn content_token(mut self, content_token: &str) -> FigureBuilder {
+        self.content_token = content_token.to_string();
+        self
+    }
+    // 表の内容を取得する時に TableContent に渡す key
+    // 省略した場合は content_token と同じにする
+    pub fn content_key(mut self, content_key: &str) -> FigureBuilder {
+        self.content_key = content_key.to_string();
+        self
+    }
+    // 表の列を左から順に足す。足さない場合は ContentKind ごとの既定の列を使う
+    pub fn auto_column(mut self, text_anchor_type: TextAnchorType) -> FigureBuilder {
+        self.columns
+            .push(ColumnStyle::new(text_anchor_type, ColumnWidth::Auto));
+        self
+    }
+    pub fn fixed_column(mut self, text_anchor_type: TextAnchorType, width: f64) -> FigureBuilder {
+        self.columns.push(ColumnStyle::new(
+            text_anchor_type,
+            ColumnWidth::Fixed(width),
+        ));
+        self
+    }
+    // 他の列を置いた残りの幅を fraction の比で分ける。内容の幅より狭くはしない
+    pub fn fraction_column(
+        mut self,
+        text_anchor_type: TextAnchorType,
+        fraction: f64,
+    ) -> FigureBuilder {
+        self.columns.push(ColumnStyle::new(
+            text_anchor_type,
+            ColumnWidth::Fraction(fraction),
+        ));
+        self
+    }
+}
+
+impl FigureBuilder {
+    fn column_styles(&self, default_columns: Vec<ColumnStyle>) -> Vec<ColumnStyle> {
+        if self.columns.is_empty() {
+            default_columns
+        } else {
+            self.columns.clone()
+        }
+    }
+    pub(crate) fn build(&self, element_manager: &mut ElementManager) -> Figure {
+        let container = element_manager.get_container();
+        let figure_id = element_manager.create_figure_group(container);
+        let content_token = if self.content_token.is_empty() {
+            format!("figure-{}", figure_id)
+        } else {
+            self.content_token.clone()
+        };
+        let content_key = if self.content_key.is_empty() {
+            content_token.as_str()
+        } else {
+            self.content_key.as_str()
+        };
+        let content_part_type = match self.content_kind {
+            ContentKind::Empty => PartType::Ignore,
+            ContentKind::Table => {
+                let mut table_content_state =
+                    TableContentState::new(content_token.as_str(), content_key);
+                table_content_state
+                    .set_column_styles(self.column_styles(ColumnStyle::default_columns()));
+                PartType::TableContent(table_content_state)
+            }
+            ContentKind::Log => {
+                let mut table_content_state =
+                    TableContentState::new(content_token.as_str(), content_key);
+                table_content_state
+                    .set_column_styles(self.column_styles(ColumnStyle::log_columns()));
+                PartType::TableContent(table_content_state)
+            }
+            ContentKind::AppendLog => {
+                PartType::LogContent(LogContentState::new(content_token.as_str(), self.max_lines))
+            }
+        };
+        let width = RectLength::new_with_min_max(self.width.0, self.width.1, self.width.2);
+        let height = RectLength::new_with_min_max(self.height.0, self.height.1, self.height.2);
+        let mut buttons = vec![];
+        if self.has_minimize_button {
+            buttons.push(ButtonType::Minimize(MinimizeOption {
+                minimized_width: width.min,
+                minimized_height: height.min,
+            }));
+        }
+        if self.has_maximize_button {
+            buttons.push(ButtonType::Maximize(MaximizeOption {}));
+        }
+        if self.has_show_content_button {
+            buttons.push(ButtonType::ShowContent(ShowContentOption {}));
+        }
+        if self.has_close_button {
+            buttons.push(ButtonType::Close);
+        }
+        let mut figure = Figure::default_window(
+            self.title.as_str(),
+            self.x,
+            self.y,
+            width,
+            height,
+            self.frame_color.as_str(),
+            self.margin,
+            self.title_height,
+            content_part_type,
+            buttons,
+            element_manager,
+            figure_id,
+        );
+        figure.base_rect.aspect_ratio = self.aspect_ratio;
+        figure.base_rect.containment = self.containment;
+        if let Some(thickness) = self.resize_handle_thickness {
+            figure.base_rect.resize_handle_thickness = thickness;
+        }
+        if self.follow_tail {
+            figure.set_follow_tail(self.margin, element_manager);
+        }
+        figure
+    }
+}
@@ -1,5 +1,10 @@
 pub mod binder;
 mod content;
+mod expression;
 mod figure;
+pub mod figure_builder;
 mod math;
+pub mod render_backend;
+pub mod state_store;
+pub mod table_style;
 mod utils;
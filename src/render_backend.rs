@@ -0,0 +1,38 @@
+pub use memory_backend::MemoryBackend;
+pub use scene_graph::SceneGraph;
+pub use web_sys_backend::WebSysBackend;
+
+mod memory_backend;
+mod scene_graph;
+mod web_sys_backend;
+
+// バックエンドが払い出す要素の識別子
+// 要素が削除された後は同じ値が再利用されることがある
+pub type NodeId = usize;
+
+// ElementManager が描画に使う操作の一覧
+// web_sys の DOM 操作に対応する最小限のものだけを持つ
+pub trait RenderBackend {
+    // Figure の g 要素を追加する先の svg 要素
+    fn container(&self) -> NodeId;
+    // parent の末尾に svg 名前空間の要素を追加する
+    fn create_element(&mut self, parent: NodeId, tag_name: &str) -> NodeId;
+    // defs 内の id 付き要素を複製して parent の末尾に追加する（子要素は複製しない）
+    // 複製した要素からは id 属性を取り除く
+    fn clone_template(&mut self, parent: NodeId, template_id: &str) -> NodeId;
+    // symbol 配下の子要素を孫要素ごと複製して parent の末尾に追加する
+    fn clone_symbol_children(&mut self, parent: NodeId, symbol_id: &str) -> Vec<NodeId>;
+    // 既に parent 配下にある要素を渡した場合は末尾への移動になる（重なり順の変更に使う）
+    fn append_child(&mut self, parent: NodeId, child: NodeId);
+    fn remove(&mut self, node: NodeId);
+    fn remove_children(&mut self, node: NodeId);
+    fn next_sibling(&self, node: NodeId) -> Option<NodeId>;
+    fn tag_name(&self, node: NodeId) -> String;
+    fn set_attribute(&mut self, node: NodeId, name: &str, value: &str);
+    fn get_attribute(&self, node: NodeId, name: &str) -> Option<String>;
+    fn remove_attribute(&mut self, node: NodeId, name: &str);
+    fn set_text(&mut self, node: NodeId, text: &str);
+    // 要素自身の座標系での (width, height)
+    // container のスケールは含まない
+    fn bounding_size(&self, node: NodeId) -> (f64, f64);
+}
@@ -0,0 +1,285 @@
+use crate::render_backend::scene_graph::{SceneGraph, SceneNode};
+use crate::render_backend::{NodeId, RenderBackend};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const INDEX_HTML: &str = include_str!("../../index.html");
+
+// ブラウザを使わずに描画結果を保持するバックエンド
+// clone したものは同じツリーを共有するので、Binder に渡した後もテストから中身を参照できる
+#[derive(Clone)]
+pub struct MemoryBackend {
+    scene_graph: Rc<RefCell<SceneGraph>>,
+}
+
+impl Default for MemoryBackend {
+    fn default() -> MemoryBackend {
+        MemoryBackend::new()
+    }
+}
+
+impl MemoryBackend {
+    // index.html の defs と同じテンプレートを持った状態で作成する
+    pub fn new() -> MemoryBackend {
+        match MemoryBackend::new_with_index_html(INDEX_HTML) {
+            Ok(backend) => backend,
+            Err(error) => panic!("index.html のテンプレートを読み込めません: {}", error),
+        }
+    }
+    // index_html の svg 要素の属性と defs の中身をテンプレートとして読み込む
+    // 読み込めない時は、読めなかった箇所を含むメッセージを返す
+    pub fn new_with_index_html(index_html: &str) -> Result<MemoryBackend, String> {
+        let backend = MemoryBackend {
+            scene_graph: Rc::new(RefCell::new(SceneGraph::new())),
+        };
+        backend.define_templates(index_html)?;
+        Ok(backend)
+    }
+
+    // テンプレートは index.html だけに書く
+    // opacity は初回の resize を隠すためのものなので使わない
+    fn define_templates(&self, index_html: &str) -> Result<(), String> {
+        let mut backend = self.clone();
+        let container = self.scene_graph.borrow().container();
+        let start = index_html.find("<svg ").ok_or("<svg> がありません")?;
+        let end = index_html[start..]
+            .find("</defs>")
+            .map(|end| start + end)
+            .ok_or("<svg> の中に </defs> がありません")?;
+        let mut parents: Vec<(&str, NodeId)> = vec![];
+        for tag in parse_tags(&index_html[start..end])? {
+            match tag {
+                Tag::End(tag_name) => match parents.pop() {
+                    Some((parent_name, _)) if parent_name == tag_name => {}
+                    _ => return Err(format!("</{}> に対応する開始タグがありません", tag_name)),
+                },
+                Tag::Start {
+                    tag_name,
+                    attributes,
+                    is_empty,
+                } => {
+                    let node = match parents.last() {
+                        Some((_, parent)) => backend.create_element(*parent, tag_name),
+                        None => container,
+                    };
+                    for (name, value) in attributes {
+                        if node != container || name != "opacity" {
+                            backend.set_attribute(node, name, value);
+                        }
+                    }
+                    if !is_empty {
+                        parents.push((tag_name, node));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // 現時点のツリーの複製
+    pub fn scene_graph(&self) -> SceneGraph {
+        self.scene_graph.borrow().clone()
+    }
+    pub fn to_svg_string(&self) -> String {
+        self.scene_graph.borrow().to_svg_string()
+    }
+    pub fn children(&self, node: NodeId) -> Vec<NodeId> {
+        self.scene_graph.borrow().children(node).to_vec()
+    }
+    pub fn text(&self, node: NodeId) -> String {
+        self.scene_graph.borrow().text(node).to_string()
+    }
+    pub fn find_by_id(&self, id: &str) -> Option<NodeId> {
+        self.scene_graph.borrow().find_by_id(id)
+    }
+}
+
+impl RenderBackend for MemoryBackend {
+    fn container(&self) -> NodeId {
+        self.scene_graph.borrow().container()
+    }
+    fn create_element(&mut self, parent: NodeId, tag_name: &str) -> NodeId {
+        let mut scene_graph = self.scene_graph.borrow_mut();
+        let id = scene_graph.insert(SceneNode::new(tag_name));
+        scene_graph.append(parent, id);
+        id
+    }
+    fn clone_template(&mut self, parent: NodeId, template_id: &str) -> NodeId {
+        let mut scene_graph = self.scene_graph.borrow_mut();
+        let source = scene_graph.find_by_id(template_id).unwrap();
+        let mut node = scene_graph.node(source).clone();
+        node.children = vec![];
+        node.parent = None;
+        node.attributes.remove("id");
+        let id = scene_graph.insert(node);
+        scene_graph.append(parent, id);
+        id
+    }
+    fn clone_symbol_children(&mut self, parent: NodeId, symbol_id: &str) -> Vec<NodeId> {
+        let mut scene_graph = self.scene_graph.borrow_mut();
+        let symbol = scene_graph.find_by_id(symbol_id).unwrap();
+        scene_graph
+            .node(symbol)
+            .children
+            .clone()
+            .into_iter()
+            .map(|child| scene_graph.deep_clone(child, parent))
+            .collect()
+    }
+    fn append_child(&mut self, parent: NodeId, child: NodeId) {
+        self.scene_graph.borrow_mut().append(parent, child);
+    }
+    fn remove(&mut self, node: NodeId) {
+        let mut scene_graph = self.scene_graph.borrow_mut();
+        scene_graph.detach(node);
+        scene_graph.free(node);
+    }
+    fn remove_children(&mut self, node: NodeId) {
+        self.scene_graph.borrow_mut().remove_children(node);
+    }
+    fn next_sibling(&self, node: NodeId) -> Option<NodeId> {
+        let scene_graph = self.scene_graph.borrow();
+        let parent = scene_graph.node(node).parent?;
+        let siblings = &scene_graph.node(parent).children;
+        let index = siblings.iter().position(|child| *child == node)?;
+        siblings.get(index + 1).copied()
+    }
+    fn tag_name(&self, node: NodeId) -> String {
+        self.scene_graph.borrow().node(node).tag_name.clone()
+    }
+    fn set_attribute(&mut self, node: NodeId, name: &str, value: &str) {
+        self.scene_graph
+            .borrow_mut()
+            .node_mut(node)
+            .attributes
+            .insert(name.to_string(), value.to_string());
+    }
+    fn get_attribute(&self, node: NodeId, name: &str) -> Option<String> {
+        self.scene_graph
+            .borrow()
+            .node(node)
+            .attributes
+            .get(name)
+            .cloned()
+    }
+    fn remove_attribute(&mut self, node: NodeId, name: &str) {
+        self.scene_graph
+            .borrow_mut()
+            .node_mut(node)
+            .attributes
+            .remove(name);
+    }
+    fn set_text(&mut self, node: NodeId, text: &str) {
+        let mut scene_graph = self.scene_graph.borrow_mut();
+        scene_graph.remove_children(node);
+        scene_graph.node_mut(node).text = text.to_string();
+    }
+    fn bounding_size(&self, node: NodeId) -> (f64, f64) {
+        match self.scene_graph.borrow().bounds(node, 16.0) {
+            Some((x1, y1, x2, y2)) => (x2 - x1, y2 - y1),
+            None => (0.0, 0.0),
+        }
+    }
+}
+
+enum Tag<'a> {
+    Start {
+        tag_name: &'a str,
+        attributes: Vec<(&'a str, &'a str)>,
+        // <rect /> のように自身で閉じている
+        is_empty: bool,
+    },
+    End(&'a str),
+}
+
+// markup の中のタグを順に読む。コメントとタグの間の文字列は読み飛ばす
+fn parse_tags(markup: &str) -> Result<Vec<Tag<'_>>, String> {
+    let mut tags = vec![];
+    let mut rest = markup;
+    while let Some(position) = rest.find('<') {
+        rest = &rest[position..];
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            let end = comment
+                .find("-->")
+                .ok_or_else(|| format!("コメントが閉じていません: {}", first_line(rest)))?;
+            rest = &comment[end + 3..];
+            continue;
+        }
+        let end =
+            tag_end(rest).ok_or_else(|| format!("タグが閉じていません: {}", first_line(rest)))?;
+        let tag = parse_tag(&rest[1..end])
+            .ok_or_else(|| format!("タグを読み込めません: {}", &rest[..=end]))?;
+        tags.push(tag);
+        rest = &rest[end + 1..];
+    }
+    Ok(tags)
+}
+
+fn first_line(markup: &str) -> &str {
+    markup.lines().next().unwrap_or_default()
+}
+
+// 引用符の外にある最初の '>' の位置
+fn tag_end(markup: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in markup.char_indices() {
+        match quote {
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '>' => return Some(index),
+            None => {}
+        }
+    }
+    None
+}
+
+// <rect id="a" rx='5'> の < と > の間を要素名と属性に分ける。値の無い属性は空文字にする
+fn parse_tag(tag: &str) -> Option<Tag<'_>> {
+    if let Some(tag_name) = tag.strip_prefix('/') {
+        return Some(Tag::End(tag_name.trim()));
+    }
+    let (tag, is_empty) = match tag.trim_end().strip_suffix('/') {
+        Some(tag) => (tag, true),
+        None => (tag, false),
+    };
+    let (tag_name, mut rest) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+    if tag_name.is_empty() {
+        return None;
+    }
+    let mut attributes = vec![];
+    rest = rest.trim_start();
+    while !rest.is_empty() {
+        let name_end = rest
+            .find(|c: char| c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let name = &rest[..name_end];
+        if name.is_empty() {
+            return None;
+        }
+        rest = rest[name_end..].trim_start();
+        let mut value = "";
+        if let Some(next) = rest.strip_prefix('=') {
+            let next = next.trim_start();
+            match next.chars().next() {
+                Some(quote) if quote == '"' || quote == '\'' => {
+                    let end = next[1..].find(quote)? + 1;
+                    value = &next[1..end];
+                    rest = &next[end + 1..];
+                }
+                _ => {
+                    let end = next.find(char::is_whitespace).unwrap_or(next.len());
+                    value = &next[..end];
+                    rest = &next[end..];
+                }
+            }
+        }
+        attributes.push((name, value));
+        rest = rest.trim_start();
+    }
+    Some(Tag::Start {
+        tag_name,
+        attributes,
+        is_empty,
+    })
+}
@@ -0,0 +1,252 @@
+use crate::render_backend::NodeId;
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug)]
+pub(crate) struct SceneNode {
+    pub(crate) tag_name: String,
+    pub(crate) attributes: BTreeMap<String, String>,
+    pub(crate) text: String,
+    pub(crate) parent: Option<NodeId>,
+    pub(crate) children: Vec<NodeId>,
+}
+
+impl SceneNode {
+    pub(crate) fn new(tag_name: &str) -> SceneNode {
+        SceneNode {
+            tag_name: tag_name.to_string(),
+            attributes: BTreeMap::new(),
+            text: "".to_string(),
+            parent: None,
+            children: vec![],
+        }
+    }
+    fn number(&self, name: &str) -> f64 {
+        self.attributes
+            .get(name)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0.0)
+    }
+}
+
+// MemoryBackend が保持する svg のツリー
+// ブラウザ上で生成される g, rect, text, tspan の構造をそのまま持つ
+#[derive(Clone, Debug)]
+pub struct SceneGraph {
+    nodes: Vec<Option<SceneNode>>,
+    container: NodeId,
+}
+
+impl SceneGraph {
+    pub(crate) fn new() -> SceneGraph {
+        SceneGraph {
+            nodes: vec![Some(SceneNode::new("svg"))],
+            container: 0,
+        }
+    }
+    pub fn container(&self) -> NodeId {
+        self.container
+    }
+    pub fn tag_name(&self, node: NodeId) -> &str {
+        self.node(node).tag_name.as_str()
+    }
+    pub fn attribute(&self, node: NodeId, name: &str) -> Option<&str> {
+        self.node(node)
+            .attributes
+            .get(name)
+            .map(|value| value.as_str())
+    }
+    pub fn children(&self, node: NodeId) -> &[NodeId] {
+        self.node(node).children.as_slice()
+    }
+    pub fn text(&self, node: NodeId) -> &str {
+        self.node(node).text.as_str()
+    }
+    // 属性は名前順、子要素は 2 スペースずつインデントして出力する
+    // 同じツリーからは常に同じ文字列が得られるので、スナップショットの比較に使える
+    pub fn to_svg_string(&self) -> String {
+        let mut result = String::new();
+        self.write_node(&mut result, self.container, 0);
+        result
+    }
+    fn write_node(&self, result: &mut String, node: NodeId, depth: usize) {
+        let target = self.node(node);
+        let indent = "  ".repeat(depth);
+        result.push_str(format!("{}<{}", indent, target.tag_name).as_str());
+        for (name, value) in target.attributes.iter() {
+            result.push_str(format!(" {}=\"{}\"", name, escape(value, true)).as_str());
+        }
+        if target.children.is_empty() {
+            if target.text.is_empty() {
+                result.push_str("/>\n");
+            } else {
+                result.push_str(
+                    format!(">{}</{}>\n", escape(&target.text, false), target.tag_name).as_str(),
+                );
+            }
+            return;
+        }
+        result.push_str(">\n");
+        if !target.text.is_empty() {
+            result.push_str(format!("{}  {}\n", indent, escape(&target.text, false)).as_str());
+        }
+        for child in target.children.iter() {
+            self.write_node(result, *child, depth + 1);
+        }
+        result.push_str(format!("{}</{}>\n", indent, target.tag_name).as_str());
+    }
+    pub(crate) fn node(&self, node: NodeId) -> &SceneNode {
+        self.nodes[node].as_ref().unwrap()
+    }
+    pub(crate) fn node_mut(&mut self, node: NodeId) -> &mut SceneNode {
+        self.nodes[node].as_mut().unwrap()
+    }
+    pub(crate) fn insert(&mut self, node: SceneNode) -> NodeId {
+        if let Some(id) = self.nodes.iter().position(|node| node.is_none()) {
+            self.nodes[id] = Some(node);
+            id
+        } else {
+            self.nodes.push(Some(node));
+            self.nodes.len() - 1
+        }
+    }
+    pub(crate) fn detach(&mut self, node: NodeId) {
+        if let Some(parent) = self.node(node).parent {
+            self.node_mut(parent)
+                .children
+                .retain(|child| *child != node);
+        }
+        self.node_mut(node).parent = None;
+    }
+    pub(crate) fn append(&mut self, parent: NodeId, child: NodeId) {
+        self.detach(child);
+        self.node_mut(child).parent = Some(parent);
+        self.node_mut(parent).children.push(child);
+    }
+    pub(crate) fn deep_clone(&mut self, source: NodeId, parent: NodeId) -> NodeId {
+        let mut node = self.node(source).clone();
+        node.children = vec![];
+        node.parent = None;
+        let id = self.insert(node);
+        self.append(parent, id);
+        for child in self.node(source).children.clone() {
+            self.deep_clone(child, id);
+        }
+        id
+    }
+    pub(crate) fn free(&mut self, node: NodeId) {
+        for child in self.node(node).children.clone() {
+            self.free(child);
+        }
+        self.nodes[node] = None;
+    }
+    pub(crate) fn remove_children(&mut self, node: NodeId) {
+        for child in self.node(node).children.clone() {
+            self.detach(child);
+            self.free(child);
+        }
+    }
+    pub fn find_by_id(&self, id: &str) -> Option<NodeId> {
+        self.nodes.iter().position(|node| match node {
+            Some(node) => node.attributes.get("id").map(|value| value.as_str()) == Some(id),
+            None => false,
+        })
+    }
+    // ブラウザの getBBox を文字幅の概算で置き換えたもの
+    // 半角は font-size の 0.6 倍、それ以外は font-size と同じ幅とみなす
+    pub(crate) fn bounds(
+        &self,
+        node: NodeId,
+        inherited_font_size: f64,
+    ) -> Option<(f64, f64, f64, f64)> {
+        let target = self.node(node);
+        let font_size = match target.attributes.get("font-size") {
+            Some(value) => value.parse().unwrap_or(inherited_font_size),
+            None => inherited_font_size,
+        };
+        match target.tag_name.as_str() {
+            "clipPath" | "defs" | "symbol" => None,
+            "rect" => {
+                let x = target.number("x");
+                let y = target.number("y");
+                Some((
+                    x,
+                    y,
+                    x + target.number("width"),
+                    y + target.number("height"),
+                ))
+            }
+            "tspan" | "text" if !target.text.is_empty() => {
+                let width: f64 = target
+                    .text
+                    .chars()
+                    .map(|c| if c.is_ascii() { 0.6 } else { 1.0 })
+                    .sum::<f64>()
+                    * font_size;
+                let x = target.number("x");
+                let baseline = target.number("y") + target.number("dy");
+                let start_x = match target.attributes.get("text-anchor").map(|a| a.as_str()) {
+                    Some("end") => x - width,
+                    Some("middle") => x - width / 2.0,
+                    _ => x,
+                };
+                Some((
+                    start_x,
+                    baseline - font_size,
+                    start_x + width,
+                    baseline + font_size * 0.25,
+                ))
+            }
+            _ => {
+                // 自身の transform は含めず、子の g の transform だけを反映する
+                let mut result: Option<(f64, f64, f64, f64)> = None;
+                for child in target.children.iter() {
+                    if let Some((x1, y1, x2, y2)) = self.bounds(*child, font_size) {
+                        let (child_x, child_y) = match self.node(*child).tag_name.as_str() {
+                            "g" => match self.node(*child).attributes.get("transform") {
+                                Some(transform) => parse_translate(transform),
+                                None => (0.0, 0.0),
+                            },
+                            _ => (0.0, 0.0),
+                        };
+                        let (x1, y1, x2, y2) =
+                            (x1 + child_x, y1 + child_y, x2 + child_x, y2 + child_y);
+                        result = Some(match result {
+                            Some((rx1, ry1, rx2, ry2)) => {
+                                (rx1.min(x1), ry1.min(y1), rx2.max(x2), ry2.max(y2))
+                            }
+                            None => (x1, y1, x2, y2),
+                        });
+                    }
+                }
+                result
+            }
+        }
+    }
+}
+
+fn parse_translate(transform: &str) -> (f64, f64) {
+    let values: Vec<f64> = transform
+        .trim_start_matches("translate(")
+        .trim_end_matches(')')
+        .split(',')
+        .filter_map(|value| value.trim().parse().ok())
+        .collect();
+    (
+        values.first().copied().unwrap_or(0.0),
+        values.get(1).copied().unwrap_or(0.0),
+    )
+}
+
+fn escape(value: &str, is_attribute: bool) -> String {
+    let mut result = String::new();
+    for c in value.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' if is_attribute => result.push_str("&quot;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
@@ -0,0 +1,209 @@
+use crate::render_backend::{NodeId, RenderBackend};
+use wasm_bindgen::JsCast;
+use web_sys::{window, Document, Element, SvgGraphicsElement};
+
+const SVG_NAMESPACE: &str = "http://www.w3.org/2000/svg";
+
+// 子要素は first_child から next をたどった順に並ぶ。DOM を探さずに兄弟と子を引けるようにする
+struct WebSysNode {
+    element: Element,
+    parent: Option<NodeId>,
+    previous: Option<NodeId>,
+    next: Option<NodeId>,
+    first_child: Option<NodeId>,
+    last_child: Option<NodeId>,
+}
+
+impl WebSysNode {
+    fn new(element: Element) -> WebSysNode {
+        WebSysNode {
+            element,
+            parent: None,
+            previous: None,
+            next: None,
+            first_child: None,
+            last_child: None,
+        }
+    }
+}
+
+// ブラウザの DOM に直接描画するバックエンド
+pub struct WebSysBackend {
+    document: Document,
+    nodes: Vec<Option<WebSysNode>>,
+    free_ids: Vec<NodeId>,
+    container: NodeId,
+}
+
+impl WebSysBackend {
+    pub fn new(container_id: &str) -> WebSysBackend {
+        let document = window().unwrap().document().unwrap();
+        let container = document.get_element_by_id(container_id).unwrap();
+        WebSysBackend {
+            document,
+            nodes: vec![Some(WebSysNode::new(container))],
+            free_ids: vec![],
+            container: 0,
+        }
+    }
+    fn element(&self, node: NodeId) -> &Element {
+        &self.nodes[node].as_ref().unwrap().element
+    }
+    fn node(&self, node: NodeId) -> &WebSysNode {
+        self.nodes[node].as_ref().unwrap()
+    }
+    fn node_mut(&mut self, node: NodeId) -> &mut WebSysNode {
+        self.nodes[node].as_mut().unwrap()
+    }
+    fn register(&mut self, element: Element, parent: NodeId) -> NodeId {
+        let node = Some(WebSysNode::new(element));
+        let id = if let Some(id) = self.free_ids.pop() {
+            self.nodes[id] = node;
+            id
+        } else {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        };
+        self.link(parent, id);
+        id
+    }
+    // parent の子の末尾につなぐ
+    fn link(&mut self, parent: NodeId, child: NodeId) {
+        let last_child = self.node(parent).last_child;
+        match last_child {
+            Some(last_child) => self.node_mut(last_child).next = Some(child),
+            None => self.node_mut(parent).first_child = Some(child),
+        }
+        self.node_mut(parent).last_child = Some(child);
+        let node = self.node_mut(child);
+        node.parent = Some(parent);
+        node.previous = last_child;
+        node.next = None;
+    }
+    // 親と兄弟とのつながりを外す
+    fn unlink(&mut self, child: NodeId) {
+        let WebSysNode {
+            parent,
+            previous,
+            next,
+            ..
+        } = *self.node(child);
+        let Some(parent) = parent else {
+            return;
+        };
+        match previous {
+            Some(previous) => self.node_mut(previous).next = next,
+            None => self.node_mut(parent).first_child = next,
+        }
+        match next {
+            Some(next) => self.node_mut(next).previous = previous,
+            None => self.node_mut(parent).last_child = previous,
+        }
+        let node = self.node_mut(child);
+        node.parent = None;
+        node.previous = None;
+        node.next = None;
+    }
+    // DOM からは取り除かれているので、登録だけを解除する
+    fn unregister_children(&mut self, parent: NodeId) {
+        let mut child = self.node(parent).first_child;
+        while let Some(id) = child {
+            child = self.node(id).next;
+            self.unregister_children(id);
+            self.nodes[id] = None;
+            self.free_ids.push(id);
+        }
+        let node = self.node_mut(parent);
+        node.first_child = None;
+        node.last_child = None;
+    }
+}
+
+impl RenderBackend for WebSysBackend {
+    fn container(&self) -> NodeId {
+        self.container
+    }
+    fn create_element(&mut self, parent: NodeId, tag_name: &str) -> NodeId {
+        let element = self
+            .document
+            .create_element_ns(Some(SVG_NAMESPACE), tag_name)
+            .unwrap();
+        self.element(parent).append_child(&element).unwrap();
+        self.register(element, parent)
+    }
+    fn clone_template(&mut self, parent: NodeId, template_id: &str) -> NodeId {
+        // get_element_by_id した要素をそのまま違う親に append_child すると要素の移動になるので clone する
+        let element: Element = self
+            .document
+            .get_element_by_id(template_id)
+            .unwrap()
+            .clone_node()
+            .unwrap()
+            .unchecked_into();
+        element.remove_attribute("id").unwrap();
+        self.element(parent).append_child(&element).unwrap();
+        self.register(element, parent)
+    }
+    fn clone_symbol_children(&mut self, parent: NodeId, symbol_id: &str) -> Vec<NodeId> {
+        let symbol = self.document.get_element_by_id(symbol_id).unwrap();
+        let symbol_children = symbol.children();
+        let mut ids = vec![];
+        // child_nodes() だと 空白Node もコピーすることになるので children() を使う
+        for n in 0..symbol_children.length() {
+            let element: Element = symbol_children
+                .item(n)
+                .unwrap()
+                .clone_node_with_deep(true)
+                .unwrap()
+                .unchecked_into();
+            self.element(parent).append_child(&element).unwrap();
+            ids.push(self.register(element, parent));
+        }
+        ids
+    }
+    fn append_child(&mut self, parent: NodeId, child: NodeId) {
+        self.element(parent)
+            .append_child(self.element(child))
+            .unwrap();
+        self.unlink(child);
+        self.link(parent, child);
+    }
+    fn remove(&mut self, node: NodeId) {
+        self.element(node).remove();
+        self.unlink(node);
+        self.unregister_children(node);
+        self.nodes[node] = None;
+        self.free_ids.push(node);
+    }
+    fn remove_children(&mut self, node: NodeId) {
+        self.element(node).set_inner_html("");
+        self.unregister_children(node);
+    }
+    fn next_sibling(&self, node: NodeId) -> Option<NodeId> {
+        self.node(node).next
+    }
+    fn tag_name(&self, node: NodeId) -> String {
+        self.element(node).tag_name()
+    }
+    fn set_attribute(&mut self, node: NodeId, name: &str, value: &str) {
+        self.element(node).set_attribute(name, value).unwrap();
+    }
+    fn get_attribute(&self, node: NodeId, name: &str) -> Option<String> {
+        self.element(node).get_attribute(name)
+    }
+    fn remove_attribute(&mut self, node: NodeId, name: &str) {
+        self.element(node).remove_attribute(name).unwrap();
+    }
+    fn set_text(&mut self, node: NodeId, text: &str) {
+        self.element(node).set_text_content(Some(text));
+    }
+    fn bounding_size(&self, node: NodeId) -> (f64, f64) {
+        match self.element(node).dyn_ref::<SvgGraphicsElement>() {
+            Some(graphics) => match graphics.get_b_box() {
+                Ok(rect) => (rect.width() as f64, rect.height() as f64),
+                Err(_) => (0.0, 0.0),
+            },
+            None => (0.0, 0.0),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use wasm_bindgen::{JsCast, JsValue};
+
+// Binder::set_state で保存する値
+#[derive(Clone, Debug, PartialEq)]
+pub enum StateValue {
+    Number(f64),
+    Bool(bool),
+    Text(String),
+    List(Vec<StateValue>),
+}
+
+impl StateValue {
+    // 数値、真偽値、文字列と、それらの配列だけ受け付ける
+    pub(crate) fn from_js(value: &JsValue) -> Option<StateValue> {
+        if let Some(number) = value.as_f64() {
+            return Some(StateValue::Number(number));
+        }
+        if let Some(value) = value.as_bool() {
+            return Some(StateValue::Bool(value));
+        }
+        if let Some(text) = value.as_string() {
+            return Some(StateValue::Text(text));
+        }
+        let array = value.dyn_ref::<js_sys::Array>()?;
+        array
+            .iter()
+            .map(|item| StateValue::from_js(&item))
+            .collect::<Option<Vec<StateValue>>>()
+            .map(StateValue::List)
+    }
+}
+
+impl std::fmt::Display for StateValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StateValue::Number(number) => write!(f, "{}", number),
+            StateValue::Bool(value) => write!(f, "{}", value),
+            StateValue::Text(text) => write!(f, "{}", text),
+            StateValue::List(items) => {
+                for (n, item) in items.iter().enumerate() {
+                    if n > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+// "player1.hp" のような path ごとに値を持つ
+// 配列の要素は "party.0" のように番号を付けて読める
+#[derive(Debug, Default)]
+pub(crate) struct StateStore {
+    values: HashMap<String, StateValue>,
+}
+
+impl StateStore {
+    pub(crate) fn set(&mut self, path: &str, value: StateValue) {
+        self.values.insert(path.to_string(), value);
+    }
+    pub(crate) fn remove(&mut self, path: &str) -> bool {
+        self.values.remove(path).is_some()
+    }
+    pub(crate) fn get(&self, path: &str) -> Option<&StateValue> {
+        if let Some(value) = self.values.get(path) {
+            return Some(value);
+        }
+        let (parent, index) = path.rsplit_once('.')?;
+        match self.get(parent)? {
+            StateValue::List(items) => items.get(index.parse::<usize>().ok()?),
+            _ => None,
+        }
+    }
+}
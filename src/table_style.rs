@@ -0,0 +1,211 @@
+use crate::binder::element_manager::ElementManager;
+use crate::expression::Condition;
+use crate::render_backend::NodeId;
+use crate::state_store::StateStore;
+use wasm_bindgen::{JsCast, JsValue};
+
+// セルの装飾。None の項目は指定しない
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CellStyle {
+    pub fill: Option<String>,
+    pub font_weight: Option<String>,
+    pub italic: Option<bool>,
+    // 行の背景。列を指定した StyleRule では使わない
+    pub background: Option<String>,
+}
+
+impl CellStyle {
+    // 指定のある項目だけ上書きする
+    fn merge(&mut self, other: &CellStyle) {
+        for (value, other) in [
+            (&mut self.fill, &other.fill),
+            (&mut self.font_weight, &other.font_weight),
+            (&mut self.background, &other.background),
+        ] {
+            if other.is_some() {
+                value.clone_from(other);
+            }
+        }
+        if other.italic.is_some() {
+            self.italic = other.italic;
+        }
+    }
+    // tspan の属性に反映する。指定の無い項目の属性は消す
+    pub(crate) fn apply(&self, element_manager: &mut ElementManager, tspan: NodeId) {
+        let italic = self.italic.and_then(|italic| italic.then_some("italic"));
+        for (name, value) in [
+            ("fill", self.fill.as_deref()),
+            ("font-weight", self.font_weight.as_deref()),
+            ("font-style", italic),
+        ] {
+            match value {
+                Some(value) => element_manager.backend.set_attribute(tspan, name, value),
+                None => element_manager.backend.remove_attribute(tspan, name),
+            }
+        }
+    }
+}
+
+// row と column で対象を絞り、when が真の時だけ style を重ねる
+// column を指定した時は、when の中で value としてそのセルの値を参照できる
+#[derive(Clone, Debug, Default)]
+pub struct StyleRule {
+    pub row: Option<usize>,
+    pub column: Option<usize>,
+    pub when: Option<String>,
+    pub style: CellStyle,
+}
+
+// 奇数行の背景を stripe にしてから、rules を順に重ねる
+#[derive(Clone, Debug, Default)]
+pub struct TableStyle {
+    pub stripe: Option<String>,
+    pub rules: Vec<StyleRule>,
+}
+
+impl TableStyle {
+    // { stripe, rules: [{ row, column, when, fill, fontWeight, italic, background }] }
+    // 省略した項目は指定しない。型が違う項目があれば、その場所を返す
+    pub(crate) fn from_js(value: &JsValue) -> Result<TableStyle, String> {
+        let rules = match get(value, "rules") {
+            Some(rules) => rules
+                .dyn_ref::<js_sys::Array>()
+                .ok_or("rules は配列で指定します")?
+                .iter()
+                .enumerate()
+                .map(|(n, rule)| {
+                    StyleRule::from_js(&rule).map_err(|error| format!("rules[{}].{}", n, error))
+                })
+                .collect::<Result<Vec<StyleRule>, String>>()?,
+            None => vec![],
+        };
+        Ok(TableStyle {
+            stripe: get_string(value, "stripe")?,
+            rules,
+        })
+    }
+}
+
+impl StyleRule {
+    fn from_js(value: &JsValue) -> Result<StyleRule, String> {
+        Ok(StyleRule {
+            row: get_index(value, "row")?,
+            column: get_index(value, "column")?,
+            when: get_string(value, "when")?,
+            style: CellStyle {
+                fill: get_string(value, "fill")?,
+                font_weight: get_string(value, "fontWeight")?,
+                italic: match get(value, "italic") {
+                    Some(italic) => Some(italic.as_bool().ok_or("italic は真偽値で指定します")?),
+                    None => None,
+                },
+                background: get_string(value, "background")?,
+            },
+        })
+    }
+}
+
+// undefined と null は省略とみなす
+fn get(value: &JsValue, name: &str) -> Option<JsValue> {
+    js_sys::Reflect::get(value, &JsValue::from_str(name))
+        .ok()
+        .filter(|value| !value.is_undefined() && !value.is_null())
+}
+
+fn get_string(value: &JsValue, name: &str) -> Result<Option<String>, String> {
+    match get(value, name) {
+        Some(value) => match value.as_string() {
+            Some(value) => Ok(Some(value)),
+            None => Err(format!("{} は文字列で指定します", name)),
+        },
+        None => Ok(None),
+    }
+}
+
+fn get_index(value: &JsValue, name: &str) -> Result<Option<usize>, String> {
+    match get(value, name) {
+        Some(value) => match value.as_f64() {
+            Some(index) if index >= 0.0 && index.fract() == 0.0 => Ok(Some(index as usize)),
+            _ => Err(format!("{} は 0 以上の整数で指定します", name)),
+        },
+        None => Ok(None),
+    }
+}
+
+// 行ごとに解決した装飾
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct RowStyle {
+    pub(crate) background: Option<String>,
+    pub(crate) cells: Vec<CellStyle>,
+}
+
+// when を解析済みの TableStyle
+pub(crate) struct CompiledTableStyle {
+    stripe: Option<String>,
+    rules: Vec<(StyleRule, Option<Condition>)>,
+}
+
+impl CompiledTableStyle {
+    pub(crate) fn new(table_style: TableStyle) -> Result<CompiledTableStyle, String> {
+        let rules = table_style
+            .rules
+            .into_iter()
+            .enumerate()
+            .map(|(n, rule)| {
+                let condition = rule
+                    .when
+                    .as_deref()
+                    .map(Condition::parse)
+                    .transpose()
+                    .map_err(|error| format!("rules[{}].when の {}", n, error))?;
+                Ok((rule, condition))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(CompiledTableStyle {
+            stripe: table_style.stripe,
+            rules,
+        })
+    }
+    // row 行目の装飾。values はその行のセルに表示している値
+    pub(crate) fn resolve(&self, row: usize, values: &[&str], store: &StateStore) -> RowStyle {
+        let mut row_style = RowStyle {
+            background: None,
+            cells: vec![CellStyle::default(); values.len()],
+        };
+        if row % 2 == 1 {
+            row_style.background.clone_from(&self.stripe);
+        }
+        for (rule, condition) in self.rules.iter() {
+            if rule.row.is_some_and(|target| target != row) {
+                continue;
+            }
+            match rule.column {
+                Some(column) => {
+                    let Some(value) = values.get(column) else {
+                        continue;
+                    };
+                    if condition
+                        .as_ref()
+                        .map_or(true, |condition| condition.is_met(store, Some(value)))
+                    {
+                        row_style.cells[column].merge(&rule.style);
+                    }
+                }
+                None => {
+                    if condition
+                        .as_ref()
+                        .map_or(true, |condition| condition.is_met(store, None))
+                    {
+                        for cell in row_style.cells.iter_mut() {
+                            cell.merge(&rule.style);
+                        }
+                        if rule.style.background.is_some() {
+                            row_style.background.clone_from(&rule.style.background);
+                        }
+                    }
+                }
+            }
+        }
+        row_style
+    }
+}
@@ -0,0 +1,140 @@
+//! push_line で行を追加するログの確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::content_group;
+use window_lib::binder::Binder;
+use window_lib::figure_builder::{ContentKind, FigureBuilder};
+use window_lib::render_backend::{MemoryBackend, NodeId, RenderBackend};
+
+// 100, 100 に表示領域の高さ 95 のログウィンドウを置く
+fn new_binder(max_lines: usize, follow_tail: bool) -> (Binder, MemoryBackend) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    binder.add_figure(
+        &FigureBuilder::new()
+            .width(300.0, 180.0, f64::INFINITY)
+            .height(130.0, 30.0, f64::INFINITY)
+            .content_kind(ContentKind::AppendLog)
+            .max_lines(max_lines)
+            .follow_tail(follow_tail)
+            .show_content_button(false),
+    );
+    binder.update();
+    (binder, backend)
+}
+
+fn push_lines(binder: &mut Binder, range: std::ops::Range<usize>) {
+    for n in range {
+        assert!(binder.push_line(0, format!("log {}", n).as_str()));
+    }
+    binder.update();
+}
+
+// clipPath の次の g の中の text
+fn line_group(backend: &MemoryBackend) -> NodeId {
+    backend.children(content_group(backend))[1]
+}
+
+fn tspans(backend: &MemoryBackend) -> Vec<NodeId> {
+    let text = backend.children(line_group(backend))[0];
+    backend.children(text)
+}
+
+fn texts(backend: &MemoryBackend) -> Vec<String> {
+    tspans(backend)
+        .into_iter()
+        .map(|tspan| backend.text(tspan))
+        .collect()
+}
+
+fn translate_y(backend: &MemoryBackend, node: NodeId) -> f64 {
+    backend
+        .get_attribute(node, "transform")
+        .map(|transform| {
+            transform
+                .trim_end_matches(')')
+                .rsplit(", ")
+                .next()
+                .unwrap()
+                .parse::<f64>()
+                .unwrap()
+        })
+        .unwrap_or(0.0)
+}
+
+// n 行目の tspan が画面上で置かれている y
+fn line_y(backend: &MemoryBackend, n: usize) -> f64 {
+    let tspan = tspans(backend)
+        .into_iter()
+        .find(|tspan| backend.text(*tspan) == format!("log {}", n))
+        .unwrap();
+    let dy: f64 = backend.get_attribute(tspan, "dy").unwrap().parse().unwrap();
+    let y = translate_y(backend, content_group(backend))
+        + translate_y(backend, line_group(backend))
+        + dy;
+    (y * 1e6).round() / 1e6
+}
+
+#[test]
+fn push_line_appends_tspans() {
+    let (mut binder, backend) = new_binder(100, false);
+    push_lines(&mut binder, 0..3);
+    assert_eq!(texts(&backend), vec!["log 0", "log 1", "log 2"]);
+    let first_tspans = tspans(&backend);
+    push_lines(&mut binder, 3..5);
+    // 描画済みの行の tspan はそのまま残る
+    assert_eq!(tspans(&backend)[..3], first_tspans[..]);
+    assert_eq!(texts(&backend).len(), 5);
+    assert_eq!(line_y(&backend, 4) - line_y(&backend, 3), 25.0);
+}
+
+#[test]
+fn push_line_needs_append_log() {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    binder.add_figure(&FigureBuilder::new().content_kind(ContentKind::Log));
+    assert!(!binder.push_line(0, "log"));
+    assert!(!binder.push_line(1, "log"));
+}
+
+#[test]
+fn old_lines_are_evicted_over_max_lines() {
+    let (mut binder, backend) = new_binder(3, false);
+    push_lines(&mut binder, 0..3);
+    let kept = tspans(&backend)[2];
+    push_lines(&mut binder, 3..5);
+    assert_eq!(texts(&backend), vec!["log 2", "log 3", "log 4"]);
+    assert_eq!(tspans(&backend)[0], kept);
+    // 捨てた 2 行分だけ上にずらして、先頭の行を最初の位置に置く
+    assert_eq!(translate_y(&backend, line_group(&backend)), -50.0);
+    // 一度に max_lines より多く追加しても、残る行だけを描く
+    push_lines(&mut binder, 5..12);
+    assert_eq!(texts(&backend), vec!["log 9", "log 10", "log 11"]);
+    assert_eq!(line_y(&backend, 9), 30.0);
+}
+
+#[test]
+fn scrolled_up_view_keeps_lines_while_evicting() {
+    let (mut binder, backend) = new_binder(10, true);
+    push_lines(&mut binder, 0..10);
+    assert!(binder.scroll_to(0, f64::NAN, 100.0));
+    binder.update();
+    let y = line_y(&backend, 6);
+    push_lines(&mut binder, 10..12);
+    assert_eq!(line_y(&backend, 6), y);
+    // 行数は変わらなくても、追加した分を未読として数える
+    assert_eq!(binder.unread_lines(0), 2);
+}
+
+#[test]
+fn view_at_end_follows_new_lines_while_evicting() {
+    let (mut binder, backend) = new_binder(10, true);
+    push_lines(&mut binder, 0..10);
+    let y = line_y(&backend, 9);
+    push_lines(&mut binder, 10..13);
+    assert_eq!(line_y(&backend, 12), y);
+    assert_eq!(binder.unread_lines(0), 0);
+}
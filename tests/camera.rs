@@ -0,0 +1,82 @@
+//! デスクトップ全体の移動と拡大縮小の確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{new_binder, transform};
+use window_lib::binder::Binder;
+
+fn touch(binder: &mut Binder, pointer_id: i32, from: (f64, f64), to: (f64, f64)) {
+    binder.pointer_down(pointer_id, "touch", from.0, from.1, 0.5, pointer_id == 10);
+    binder.pointer_move(pointer_id, "touch", to.0, to.1, 0.5, pointer_id == 10);
+}
+
+#[test]
+fn drags_figure_through_camera() {
+    let (mut binder, backend) = new_binder();
+    binder.set_camera(100.0, 50.0, 2.0);
+    binder.update();
+    assert_eq!(transform(&backend), "translate(300, 250) scale(2)");
+    // デスクトップ上の (150, 110) は view では (400, 270) に映る
+    binder.mouse_down(400.0, 270.0);
+    binder.mouse_move(440.0, 270.0);
+    binder.mouse_up(440.0, 270.0);
+    binder.update();
+    assert_eq!(transform(&backend), "translate(340, 250) scale(2)");
+    binder.reset_camera();
+    binder.update();
+    assert_eq!(transform(&backend), "translate(120, 100)");
+}
+
+#[test]
+fn zooms_around_pointer_by_wheel() {
+    let (mut binder, backend) = new_binder();
+    binder.wheel_zoom(250.0, 110.0, -100.0);
+    binder.update();
+    let values: Vec<f64> = transform(&backend)
+        .split(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-'))
+        .filter_map(|value| value.parse().ok())
+        .collect();
+    let (x, y, zoom) = (values[0], values[1], values[2]);
+    assert!(zoom > 1.0);
+    // ポインターの下にあった Figure 内の点 (150, 10) は動かない
+    assert!(((250.0 - x) / zoom - 150.0).abs() < 1e-9);
+    assert!(((110.0 - y) / zoom - 10.0).abs() < 1e-9);
+}
+
+#[test]
+fn pinches_background_to_zoom() {
+    let (mut binder, backend) = new_binder();
+    touch(&mut binder, 10, (600.0, 500.0), (600.0, 500.0));
+    touch(&mut binder, 11, (700.0, 500.0), (800.0, 500.0));
+    binder.pointer_up(11, 800.0, 500.0);
+    binder.pointer_up(10, 600.0, 500.0);
+    binder.update();
+    // (650, 500) を中心に 2 倍にしてから、中点の移動分 (50, 0) だけずらす
+    assert_eq!(transform(&backend), "translate(-400, -300) scale(2)");
+}
+
+#[test]
+fn pans_background_with_two_fingers() {
+    let (mut binder, backend) = new_binder();
+    touch(&mut binder, 10, (600.0, 500.0), (600.0, 500.0));
+    touch(&mut binder, 11, (700.0, 500.0), (700.0, 500.0));
+    binder.pointer_move(10, "touch", 700.0, 550.0, 0.5, true);
+    binder.pointer_move(11, "touch", 800.0, 550.0, 0.5, false);
+    binder.pointer_up(10, 700.0, 550.0);
+    binder.pointer_up(11, 800.0, 550.0);
+    binder.update();
+    assert_eq!(transform(&backend), "translate(200, 150)");
+}
+
+#[test]
+fn does_not_pinch_while_dragging_figure() {
+    let (mut binder, backend) = new_binder();
+    touch(&mut binder, 10, (150.0, 110.0), (150.0, 110.0));
+    touch(&mut binder, 11, (700.0, 500.0), (800.0, 600.0));
+    binder.pointer_up(11, 800.0, 600.0);
+    binder.pointer_up(10, 150.0, 110.0);
+    binder.update();
+    assert_eq!(transform(&backend), "translate(100, 100)");
+}
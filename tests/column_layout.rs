@@ -0,0 +1,134 @@
+//! 表の列の幅を内容と表示領域の幅から決めて並べる確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{drag, strings};
+use window_lib::binder::Binder;
+use window_lib::figure_builder::{FigureBuilder, TextAnchorType};
+use window_lib::render_backend::{MemoryBackend, RenderBackend};
+
+fn set_rows(binder: &mut Binder, names: &[&str]) {
+    binder.set_table_rows(
+        "status",
+        strings(&["名前", "HP", "状態"]),
+        names
+            .iter()
+            .map(|name| strings(&[name, "50", "ok"]))
+            .collect(),
+    );
+}
+
+// 100, 100 に幅 width の 3 列の表を置く。表示領域の幅は width - 10
+fn new_binder(width: f64) -> (Binder, MemoryBackend) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    set_rows(&mut binder, &["勇者", "魔法使い"]);
+    binder.add_figure(
+        &FigureBuilder::new()
+            .content_token("status")
+            .width(width, 180.0, f64::INFINITY)
+            .height(200.0, 30.0, f64::INFINITY)
+            .show_content_button(false)
+            .auto_column(TextAnchorType::Start)
+            .fraction_column(TextAnchorType::Middle, 1.0)
+            .fixed_column(TextAnchorType::End, 40.0),
+    );
+    binder.update();
+    (binder, backend)
+}
+
+// 見出しと本体の各 tspan の x。列の中ではすべて同じになる
+fn column_x(backend: &MemoryBackend, column: usize) -> f64 {
+    let xs: Vec<String> = ["thead", "tbody"]
+        .iter()
+        .flat_map(|part| {
+            let text = backend
+                .find_by_id(format!("status-{}-col-{}", part, column).as_str())
+                .unwrap();
+            backend.children(text)
+        })
+        .map(|tspan| backend.get_attribute(tspan, "x").unwrap())
+        .collect();
+    assert!(xs.iter().all(|x| *x == xs[0]), "{:?}", xs);
+    xs[0].parse().unwrap()
+}
+
+fn column_xs(backend: &MemoryBackend) -> Vec<f64> {
+    (0..3).map(|n| column_x(backend, n)).collect()
+}
+
+// 列を指定しない時は、内容の幅の列と残りの幅の右端に寄せた列の 2 列になる
+fn new_default_binder(tbody: Vec<Vec<String>>) -> (Binder, MemoryBackend) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    binder.set_table_rows("status", strings(&["名前", "HP"]), tbody);
+    binder.add_figure(
+        &FigureBuilder::new()
+            .content_token("status")
+            .width(300.0, 180.0, f64::INFINITY)
+            .height(200.0, 30.0, f64::INFINITY)
+            .show_content_button(false),
+    );
+    binder.update();
+    (binder, backend)
+}
+
+#[test]
+fn columns_follow_content_and_window_width() {
+    let (mut binder, backend) = new_binder(300.0);
+    // 幅 270 に、内容の幅 80 の列、残りの 125 を使う列、幅 40 の列を 10 ずつ空けて並べる
+    assert_eq!(column_xs(&backend), vec![5.0, 157.5, 270.0]);
+    let tspan = backend.children(backend.find_by_id("status-tbody-col-1").unwrap())[0];
+    assert_eq!(
+        backend.get_attribute(tspan, "text-anchor"),
+        Some("middle".to_string())
+    );
+    // 右の辺を 50 広げると、残りの幅を使う列とその右の列が動く
+    drag(&mut binder, (398.0, 200.0), (448.0, 200.0));
+    assert_eq!(column_xs(&backend), vec![5.0, 182.5, 320.0]);
+}
+
+#[test]
+fn columns_are_not_narrower_than_content() {
+    let (_, backend) = new_binder(180.0);
+    // 残りの幅が 5 しかなくても、内容の幅 24 は確保する
+    assert_eq!(column_xs(&backend), vec![5.0, 107.0, 169.0]);
+}
+
+#[test]
+fn widths_are_measured_again_when_content_changes() {
+    let (mut binder, backend) = new_binder(300.0);
+    set_rows(&mut binder, &["勇者", "戦士"]);
+    binder.update();
+    // 名前の列が 40 に縮んだ分を、残りの幅を使う列が広がって埋める
+    assert_eq!(column_xs(&backend), vec![5.0, 137.5, 270.0]);
+    let tspan = backend.children(backend.find_by_id("status-tbody-col-2").unwrap())[0];
+    assert_eq!(
+        backend.get_attribute(tspan, "text-anchor"),
+        Some("end".to_string())
+    );
+}
+
+#[test]
+fn default_columns_align_values_to_right_edge() {
+    let (mut binder, backend) =
+        new_default_binder(vec![strings(&["勇者", "50"]), strings(&["魔法使い", "8"])]);
+    // 値の列は表示領域の幅 290 から縦のスクロールバーと余白を除いた 270 で右に揃える
+    assert_eq!((column_x(&backend, 0), column_x(&backend, 1)), (5.0, 270.0));
+    let tspan = backend.children(backend.find_by_id("status-tbody-col-1").unwrap())[0];
+    assert_eq!(
+        backend.get_attribute(tspan, "text-anchor"),
+        Some("end".to_string())
+    );
+    drag(&mut binder, (398.0, 200.0), (448.0, 200.0));
+    assert_eq!((column_x(&backend, 0), column_x(&backend, 1)), (5.0, 320.0));
+}
+
+#[test]
+fn default_columns_overflow_when_values_are_long() {
+    let (_, backend) = new_default_binder(vec![strings(&["勇者", &"9".repeat(30)])]);
+    // 値の列は内容の幅 360 まで広がり、表示領域から横にはみ出す
+    assert_eq!((column_x(&backend, 0), column_x(&backend, 1)), (5.0, 415.0));
+}
@@ -0,0 +1,117 @@
+//! tests の各ファイルで使う Binder の用意と要素の取得
+
+// ファイルごとに使う関数が違うので、使わない関数があっても警告しない
+#![allow(dead_code)]
+
+use window_lib::binder::Binder;
+use window_lib::figure_builder::FigureBuilder;
+use window_lib::render_backend::{MemoryBackend, NodeId, RenderBackend};
+
+// ウィンドウの無い Binder
+pub fn new_empty_binder() -> (Binder, MemoryBackend) {
+    let backend = MemoryBackend::new();
+    let binder = Binder::new_with_backend(Box::new(backend.clone()));
+    (binder, backend)
+}
+
+// 100, 100 に 300 x 200 のウィンドウを 1 つ置く
+pub fn new_binder() -> (Binder, MemoryBackend) {
+    new_binder_with(FigureBuilder::new())
+}
+
+// figure_builder を 300 x 200 にして追加する
+pub fn new_binder_with(figure_builder: FigureBuilder) -> (Binder, MemoryBackend) {
+    let (mut binder, backend) = new_empty_binder();
+    binder.add_figure(
+        &figure_builder
+            .width(300.0, 180.0, f64::INFINITY)
+            .height(200.0, 30.0, f64::INFINITY)
+            .show_content_button(false),
+    );
+    binder.update();
+    (binder, backend)
+}
+
+// Binder::new_for_dev と同じウィンドウと内容
+pub fn new_dev_binder() -> (Binder, MemoryBackend) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_for_dev_with_backend(Box::new(backend.clone()));
+    binder.set_dummy_state();
+    binder.update();
+    (binder, backend)
+}
+
+// ウィンドウごとの g。手前のものほど後ろに並ぶ
+pub fn figure_groups(backend: &MemoryBackend) -> Vec<NodeId> {
+    backend
+        .children(backend.container())
+        .into_iter()
+        .filter(|node| backend.tag_name(*node) == "g")
+        .collect()
+}
+
+pub fn figure_group(backend: &MemoryBackend) -> NodeId {
+    figure_groups(backend)[0]
+}
+
+// Scrollable の内容を入れる g
+pub fn content_group(backend: &MemoryBackend) -> NodeId {
+    backend.children(figure_group(backend))[3]
+}
+
+// content_group の translate。スクロール量の計算誤差は丸める
+pub fn content_offset(backend: &MemoryBackend) -> (f64, f64) {
+    let transform = backend
+        .get_attribute(content_group(backend), "transform")
+        .unwrap();
+    let values: Vec<f64> = transform
+        .trim_start_matches("translate(")
+        .trim_end_matches(')')
+        .split(", ")
+        .map(|value| (value.parse::<f64>().unwrap() * 1e6).round() / 1e6)
+        .collect();
+    (values[0], values[1])
+}
+
+pub fn transform(backend: &MemoryBackend) -> String {
+    backend
+        .get_attribute(figure_group(backend), "transform")
+        .unwrap()
+}
+
+// (transform, width, height)
+pub fn geometry(backend: &MemoryBackend) -> (String, String, String) {
+    let base_rect = backend.children(figure_group(backend))[0];
+    (
+        transform(backend),
+        backend.get_attribute(base_rect, "width").unwrap(),
+        backend.get_attribute(base_rect, "height").unwrap(),
+    )
+}
+
+// id の text の各 tspan の文字列
+pub fn texts(backend: &MemoryBackend, id: &str) -> Vec<String> {
+    let column = backend.find_by_id(id).unwrap();
+    backend
+        .children(column)
+        .into_iter()
+        .map(|tspan| backend.text(tspan))
+        .collect()
+}
+
+pub fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+pub fn drag(binder: &mut Binder, from: (f64, f64), to: (f64, f64)) {
+    binder.mouse_down(from.0, from.1);
+    binder.mouse_move(to.0, to.1);
+    binder.mouse_up(to.0, to.1);
+    binder.update();
+}
+
+pub fn click(binder: &mut Binder, x: f64, y: f64) {
+    binder.mouse_down(x, y);
+    binder.mouse_up(x, y);
+    binder.update();
+}
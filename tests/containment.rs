@@ -0,0 +1,95 @@
+//! ウィンドウを container の内側に留める処理の確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{drag, figure_group, new_binder_with, transform};
+use window_lib::binder::Binder;
+use window_lib::figure_builder::{Containment, FigureBuilder};
+use window_lib::render_backend::{MemoryBackend, RenderBackend};
+
+fn width(backend: &MemoryBackend) -> String {
+    let base_rect = backend.children(figure_group(backend))[0];
+    backend.get_attribute(base_rect, "width").unwrap()
+}
+
+#[test]
+fn keeps_window_inside_right_bottom_edges() {
+    let (mut binder, backend) = new_binder_with(FigureBuilder::new());
+    drag(&mut binder, (150.0, 110.0), (2000.0, 2000.0));
+    assert_eq!(transform(&backend), "translate(900, 600)");
+}
+
+#[test]
+fn keeps_title_bar_visible() {
+    let (mut binder, backend) =
+        new_binder_with(FigureBuilder::new().containment(Containment::TitleBarVisible));
+    drag(&mut binder, (150.0, 110.0), (2000.0, 2000.0));
+    assert_eq!(transform(&backend), "translate(1160, 760)");
+    drag(&mut binder, (1170.0, 770.0), (-2000.0, -2000.0));
+    assert_eq!(transform(&backend), "translate(-260, 0)");
+}
+
+#[test]
+fn allows_unconstrained_window_to_leave_container() {
+    let (mut binder, backend) =
+        new_binder_with(FigureBuilder::new().containment(Containment::Unconstrained));
+    drag(&mut binder, (150.0, 110.0), (1550.0, 60.0));
+    assert_eq!(transform(&backend), "translate(1500, 50)");
+}
+
+#[test]
+fn stops_resizing_at_right_edge() {
+    let (mut binder, backend) = new_binder_with(FigureBuilder::new().position(800.0, 100.0));
+    drag(&mut binder, (1098.0, 298.0), (1300.0, 298.0));
+    assert_eq!(width(&backend), "400");
+}
+
+#[test]
+fn clamps_windows_on_add_figure_and_set_ref_points() {
+    let (mut binder, backend) = new_binder_with(FigureBuilder::new().position(1100.0, 700.0));
+    assert_eq!(transform(&backend), "translate(900, 600)");
+    binder.set_ref_points(0.0, 0.0, 400.0);
+    binder.update();
+    assert_eq!(transform(&backend), "translate(900, 600)");
+}
+
+#[test]
+fn clamps_window_after_reset_size() {
+    let (mut binder, backend) = new_binder_with(FigureBuilder::new());
+    // 200x100 に縮めてから右下の隅に寄せる
+    drag(&mut binder, (398.0, 298.0), (298.0, 198.0));
+    drag(&mut binder, (150.0, 110.0), (2000.0, 2000.0));
+    assert_eq!(transform(&backend), "translate(1000, 700)");
+    assert!(binder.reset_size(0));
+    binder.update();
+    assert_eq!(width(&backend), "300");
+    assert_eq!(transform(&backend), "translate(900, 600)");
+}
+
+#[test]
+fn clamps_window_grown_to_show_content() {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    binder.set_table_rows(
+        "status",
+        vec![],
+        vec![vec!["とても長い名前の勇者".to_string(); 3]],
+    );
+    binder.add_figure(
+        &FigureBuilder::new()
+            .content_token("status")
+            .position(1000.0, 100.0)
+            .width(180.0, 180.0, f64::INFINITY)
+            .height(200.0, 30.0, f64::INFINITY),
+    );
+    binder.update();
+    // 内容に合わせて広げた分だけ左に戻す
+    let width: f64 = width(&backend).parse().unwrap();
+    assert!(width > 200.0);
+    assert_eq!(
+        transform(&backend),
+        format!("translate({}, 100)", 1200.0 - width)
+    );
+}
@@ -0,0 +1,85 @@
+//! key ごとに登録した TableContent から表の内容を取得することの確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::new_empty_binder;
+use window_lib::binder::{Binder, TableContent};
+use window_lib::figure_builder::FigureBuilder;
+use window_lib::render_backend::MemoryBackend;
+
+// 受け取った key と名前を 1 行だけ返す
+struct Named {
+    name: &'static str,
+}
+
+impl TableContent for Named {
+    fn get_tbody(&self, key: &str) -> Vec<Vec<String>> {
+        vec![vec![key.to_string(), self.name.to_string()]]
+    }
+}
+
+fn add_table(binder: &mut Binder, content_token: &str, content_key: &str) -> usize {
+    let mut figure_builder = FigureBuilder::new().content_token(content_token);
+    if !content_key.is_empty() {
+        figure_builder = figure_builder.content_key(content_key);
+    }
+    binder.add_figure(&figure_builder)
+}
+
+// 表の 1 行目の値
+fn first_row(backend: &MemoryBackend, content_token: &str) -> Vec<String> {
+    (0..2)
+        .map(|n| {
+            let column = backend
+                .find_by_id(format!("{}-tbody-col-{}", content_token, n).as_str())
+                .unwrap();
+            backend.text(backend.children(column)[0])
+        })
+        .collect()
+}
+
+#[test]
+fn tables_with_different_keys_show_different_content() {
+    let (mut binder, backend) = new_empty_binder();
+    binder.set_table_content("player1", Box::new(Named { name: "勇者" }));
+    binder.set_table_content("player2", Box::new(Named { name: "魔王" }));
+    add_table(&mut binder, "status1", "player1");
+    add_table(&mut binder, "status2", "player2");
+    binder.update();
+    assert_eq!(first_row(&backend, "status1"), vec!["player1", "勇者"]);
+    assert_eq!(first_row(&backend, "status2"), vec!["player2", "魔王"]);
+}
+
+#[test]
+fn unregistered_key_falls_back_to_shared_content() {
+    let (mut binder, backend) = new_empty_binder();
+    binder.set_table_content_state(Box::new(Named { name: "共通" }));
+    binder.set_table_content("player1", Box::new(Named { name: "勇者" }));
+    add_table(&mut binder, "status1", "player1");
+    // key を省略すると content_token が key になる
+    add_table(&mut binder, "status2", "");
+    binder.update();
+    assert_eq!(first_row(&backend, "status1"), vec!["player1", "勇者"]);
+    assert_eq!(first_row(&backend, "status2"), vec!["status2", "共通"]);
+    // 登録を外すと共通の内容に戻る
+    assert!(binder.remove_table_content("player1"));
+    assert!(!binder.remove_table_content("player1"));
+    binder.update();
+    assert_eq!(first_row(&backend, "status1"), vec!["player1", "共通"]);
+}
+
+#[test]
+fn content_can_be_registered_for_a_figure() {
+    let (mut binder, backend) = new_empty_binder();
+    let figure_id = binder.add_figure(&FigureBuilder::new());
+    let token = format!("figure-{}", figure_id);
+    assert!(binder.set_figure_table_content(figure_id, Box::new(Named { name: "村人" })));
+    binder.update();
+    assert_eq!(
+        first_row(&backend, token.as_str()),
+        vec![token.as_str(), "村人"]
+    );
+    assert!(!binder.set_figure_table_content(figure_id + 1, Box::new(Named { name: "-" })));
+}
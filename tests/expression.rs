@@ -0,0 +1,199 @@
+//! 表の値に書いた {} の中の式を評価する確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::cell::RefCell;
+use std::rc::Rc;
+use window_lib::binder::{Binder, TableContent};
+use window_lib::figure_builder::FigureBuilder;
+use window_lib::render_backend::MemoryBackend;
+use window_lib::state_store::StateValue;
+
+// 1 列だけの表
+struct Values {
+    values: Vec<String>,
+}
+
+impl TableContent for Values {
+    fn get_tbody(&self, _key: &str) -> Vec<Vec<String>> {
+        self.values
+            .iter()
+            .map(|value| vec![value.clone()])
+            .collect()
+    }
+    fn uses_templates(&self) -> bool {
+        true
+    }
+}
+
+fn new_binder(values: &[&str]) -> (Binder, MemoryBackend, Rc<RefCell<Vec<String>>>) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    let errors = Rc::new(RefCell::new(vec![]));
+    let errors_clone = errors.clone();
+    binder.set_on_template_error_handler(Box::new(move |message| {
+        errors_clone.borrow_mut().push(message.to_string());
+    }));
+    binder.set_table_content_state(Box::new(Values {
+        values: values.iter().map(|value| value.to_string()).collect(),
+    }));
+    binder.add_figure(&FigureBuilder::new().content_token("status"));
+    (binder, backend, errors)
+}
+
+fn values(backend: &MemoryBackend) -> Vec<String> {
+    let column = backend.find_by_id("status-tbody-col-0").unwrap();
+    backend
+        .children(column)
+        .into_iter()
+        .map(|tspan| backend.text(tspan))
+        .collect()
+}
+
+fn number(value: f64) -> StateValue {
+    StateValue::Number(value)
+}
+
+#[test]
+fn arithmetic_and_conditionals() {
+    let (mut binder, backend, errors) = new_binder(&[
+        "{hp * 100 / mhp}%",
+        "{(hp + 2) % 7}",
+        "{first ? '先攻' : \"後攻\"}",
+        "{hp < mhp / 2 && !first ? 'ピンチ' : 'ok'}",
+        "{name + ' Lv' + level}",
+    ]);
+    binder.set_state_value("hp", number(15.0));
+    binder.set_state_value("mhp", number(60.0));
+    binder.set_state_value("first", StateValue::Bool(true));
+    binder.set_state_value("name", StateValue::Text("勇者".to_string()));
+    binder.set_state_value("level", number(3.0));
+    binder.update();
+    assert_eq!(values(&backend), vec!["25%", "3", "先攻", "ok", "勇者 Lv3"]);
+    binder.set_state_value("first", StateValue::Bool(false));
+    binder.update();
+    assert_eq!(
+        values(&backend),
+        vec!["25%", "3", "後攻", "ピンチ", "勇者 Lv3"]
+    );
+    assert!(errors.borrow().is_empty());
+}
+
+#[test]
+fn numbers_can_be_formatted() {
+    let (mut binder, backend, _) = new_binder(&[
+        "{fixed(rate, 2)}",
+        "{sep(gold)}G",
+        "{sep(-1234567.5)}",
+        "[{pad(hp, 4)}]",
+        "{pad(hp, 4, '0')}",
+        "[{pad_end(hp, 3)}]",
+        "{count} {plural(count, 'item', 'items')}",
+        "{plural(len(party), '1 人', len(party) + ' 人')}",
+        "{gold / 0}",
+    ]);
+    binder.set_state_value("rate", number(1.0 / 3.0));
+    binder.set_state_value("gold", number(1234567.0));
+    binder.set_state_value("hp", number(42.0));
+    binder.set_state_value("count", number(1.0));
+    binder.set_state_value(
+        "party",
+        StateValue::List(vec![StateValue::Text("勇者".to_string())]),
+    );
+    binder.update();
+    assert_eq!(
+        values(&backend),
+        vec![
+            "0.33",
+            "1,234,567G",
+            "-1,234,567.5",
+            "[  42]",
+            "0042",
+            "[42 ]",
+            "1 item",
+            "1 人",
+            ""
+        ]
+    );
+    binder.set_state_value("count", number(2.0));
+    binder.set_state_value(
+        "party",
+        StateValue::List(vec![
+            StateValue::Text("勇者".to_string()),
+            StateValue::Text("戦士".to_string()),
+        ]),
+    );
+    binder.update();
+    assert_eq!(values(&backend)[6..8], ["2 items", "2 人"]);
+}
+
+#[test]
+fn parse_errors_are_reported_once() {
+    let (mut binder, backend, errors) = new_binder(&["{hp +}", "{hp}", "{unknown(1)}"]);
+    binder.set_state_value("hp", number(7.0));
+    binder.update();
+    // 解析できない値はそのまま表示する
+    assert_eq!(values(&backend), vec!["{hp +}", "7", "{unknown(1)}"]);
+    assert_eq!(
+        *errors.borrow(),
+        vec![
+            "status の 0 行 0 列目: 6 文字目: 式がありません",
+            "status の 2 行 0 列目: 2 文字目: 'unknown' という関数はありません",
+        ]
+    );
+    // 同じ値は解析し直さない
+    binder.set_state_value("hp", number(8.0));
+    binder.update();
+    assert_eq!(values(&backend), vec!["{hp +}", "8", "{unknown(1)}"]);
+    assert_eq!(errors.borrow().len(), 2);
+}
+
+#[test]
+fn templates_can_be_checked_before_use() {
+    assert_eq!(Binder::check_template("{hp}/{mhp}"), Ok(()));
+    assert_eq!(
+        Binder::check_template("HP {hp"),
+        Err("4 文字目: '{' が閉じていません".to_string())
+    );
+    assert_eq!(
+        Binder::check_template("{a ? 1}"),
+        Err("7 文字目: ':' がありません".to_string())
+    );
+    assert_eq!(
+        Binder::check_template("{pad(1)}"),
+        Err("2 文字目: pad の引数は 2 から 3 個です".to_string())
+    );
+    assert_eq!(
+        Binder::check_template("a}"),
+        Err("2 文字目: '}' を表示する時は '}}' と書きます".to_string())
+    );
+    assert_eq!(
+        Binder::check_template("{'abc}"),
+        Err("2 文字目: 文字列が閉じていません".to_string())
+    );
+}
+
+#[test]
+fn deep_nesting_is_rejected() {
+    let parens = format!("{{{}1{}}}", "(".repeat(3000), ")".repeat(3000));
+    let negations = format!("{{{}1}}", "-".repeat(3000));
+    let additions = format!("{{1{}}}", "+1".repeat(3000));
+    for template in [&parens, &negations, &additions].iter() {
+        assert!(Binder::check_template(template)
+            .unwrap_err()
+            .ends_with("式の入れ子が深すぎます"));
+    }
+    // 表の値でも、解析できなかった値としてそのまま表示する
+    let (mut binder, backend, errors) = new_binder(&[negations.as_str(), "{-(-1)}"]);
+    binder.update();
+    assert_eq!(values(&backend), vec![negations.as_str(), "1"]);
+    assert_eq!(errors.borrow().len(), 1);
+}
+
+#[test]
+fn padding_width_is_limited() {
+    let (mut binder, backend, _) = new_binder(&["{pad('', 4000000000)}", "{pad_end(1, 3, '-')}"]);
+    binder.update();
+    assert_eq!(values(&backend)[0].len(), 1024);
+    assert_eq!(values(&backend)[1], "1--");
+}
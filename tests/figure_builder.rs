@@ -0,0 +1,432 @@
+//! FigureBuilder で追加したウィンドウの確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{click, drag, figure_groups, geometry, new_empty_binder};
+use std::cell::RefCell;
+use std::rc::Rc;
+use window_lib::binder::Binder;
+use window_lib::figure_builder::{ContentKind, FigureBuilder};
+use window_lib::render_backend::{MemoryBackend, NodeId, RenderBackend};
+
+fn count_tag(backend: &MemoryBackend, node: NodeId, tag_name: &str) -> usize {
+    backend
+        .children(node)
+        .into_iter()
+        .map(|child| {
+            let own = if backend.tag_name(child) == tag_name {
+                1
+            } else {
+                0
+            };
+            own + count_tag(backend, child, tag_name)
+        })
+        .sum()
+}
+
+#[test]
+fn adds_figure_with_title_and_position() {
+    let (mut binder, backend) = new_empty_binder();
+    let figure_id = binder.add_figure(
+        &FigureBuilder::new()
+            .title("ステータス")
+            .position(40.0, 60.0)
+            .frame_color("#036"),
+    );
+    binder.update();
+    assert_eq!(figure_id, 0);
+    let group = figure_groups(&backend)[0];
+    assert_eq!(
+        backend.get_attribute(group, "transform").unwrap(),
+        "translate(40, 60)"
+    );
+    let base_rect = backend.children(group)[0];
+    assert_eq!(backend.get_attribute(base_rect, "fill").unwrap(), "#036");
+    let title = backend.children(group)[7];
+    assert_eq!(backend.text(title), "ステータス");
+}
+
+#[test]
+fn returns_different_ids() {
+    let (mut binder, _) = new_empty_binder();
+    let first = binder.add_figure(&FigureBuilder::new());
+    let second = binder.add_figure(&FigureBuilder::new().position(400.0, 100.0));
+    assert_ne!(first, second);
+}
+
+#[test]
+fn hides_buttons() {
+    let (mut binder, backend) = new_empty_binder();
+    binder.add_figure(&FigureBuilder::new());
+    binder.add_figure(
+        &FigureBuilder::new()
+            .minimize_button(false)
+            .show_content_button(false),
+    );
+    binder.update();
+    let groups = figure_groups(&backend);
+    // rect + g を 1 ボタンとして数える。末尾はサイズ変更用の g
+    assert_eq!(backend.children(groups[0]).len(), 13);
+    assert_eq!(backend.children(groups[1]).len(), 9);
+}
+
+#[test]
+fn keeps_size_without_show_content_button() {
+    let (mut binder, backend) = new_empty_binder();
+    binder.add_figure(
+        &FigureBuilder::new()
+            .width(320.0, 100.0, f64::INFINITY)
+            .height(240.0, 50.0, f64::INFINITY)
+            .show_content_button(false),
+    );
+    binder.update();
+    let base_rect = backend.children(figure_groups(&backend)[0])[0];
+    assert_eq!(backend.get_attribute(base_rect, "width").unwrap(), "320");
+    assert_eq!(backend.get_attribute(base_rect, "height").unwrap(), "240");
+}
+
+#[test]
+fn renders_content_by_kind() {
+    let (mut binder, backend) = new_empty_binder();
+    binder.set_dummy_state();
+    binder.add_figure(&FigureBuilder::new().content_kind(ContentKind::Table));
+    binder.add_figure(&FigureBuilder::new().content_kind(ContentKind::Log));
+    binder.add_figure(&FigureBuilder::new().content_kind(ContentKind::Empty));
+    binder.update();
+    let groups = figure_groups(&backend);
+    assert_eq!(count_tag(&backend, groups[0], "tspan"), 6);
+    assert_eq!(count_tag(&backend, groups[1], "tspan"), 3);
+    assert_eq!(count_tag(&backend, groups[2], "tspan"), 0);
+}
+
+#[test]
+fn drags_added_figure() {
+    let (mut binder, backend) = new_empty_binder();
+    binder.add_figure(&FigureBuilder::new().show_content_button(false));
+    binder.add_figure(
+        &FigureBuilder::new()
+            .position(500.0, 100.0)
+            .show_content_button(false),
+    );
+    binder.update();
+    let group = figure_groups(&backend)[1];
+    binder.mouse_down(550.0, 110.0);
+    binder.mouse_move(560.0, 130.0);
+    binder.mouse_up(560.0, 130.0);
+    binder.update();
+    assert_eq!(
+        backend.get_attribute(group, "transform").unwrap(),
+        "translate(510, 120)"
+    );
+}
+
+#[test]
+fn grabs_front_most_of_overlapping_figures() {
+    let (mut binder, backend) = new_empty_binder();
+    binder.add_figure(&FigureBuilder::new().show_content_button(false));
+    binder.add_figure(
+        &FigureBuilder::new()
+            .position(150.0, 100.0)
+            .show_content_button(false),
+    );
+    binder.update();
+    let groups = figure_groups(&backend);
+    // 後から追加した方が手前にあるので、重なっている所では後の方をつかむ
+    drag(&mut binder, (200.0, 110.0), (200.0, 210.0));
+    assert_eq!(
+        backend.get_attribute(groups[0], "transform").unwrap(),
+        "translate(100, 100)"
+    );
+    assert_eq!(
+        backend.get_attribute(groups[1], "transform").unwrap(),
+        "translate(150, 200)"
+    );
+}
+
+#[test]
+fn brings_figure_to_front_by_id() {
+    let (mut binder, backend) = new_empty_binder();
+    let first = binder.add_figure(&FigureBuilder::new().show_content_button(false));
+    binder.add_figure(
+        &FigureBuilder::new()
+            .position(150.0, 100.0)
+            .show_content_button(false),
+    );
+    binder.update();
+    let groups = figure_groups(&backend);
+    binder.bring_to_front(first);
+    assert_eq!(figure_groups(&backend).last(), Some(&groups[0]));
+    drag(&mut binder, (200.0, 110.0), (200.0, 210.0));
+    assert_eq!(
+        backend.get_attribute(groups[0], "transform").unwrap(),
+        "translate(100, 200)"
+    );
+}
+
+#[test]
+fn closes_figure_by_close_button() {
+    let (mut binder, backend) = new_empty_binder();
+    let closed = Rc::new(RefCell::new(vec![]));
+    let closed_in_handler = closed.clone();
+    binder.set_on_close_handler(Box::new(move |figure_id| {
+        closed_in_handler.borrow_mut().push(figure_id)
+    }));
+    binder.add_figure(&FigureBuilder::new().show_content_button(false));
+    let figure_id = binder.add_figure(
+        &FigureBuilder::new()
+            .position(400.0, 100.0)
+            .show_content_button(false)
+            .close_button(true),
+    );
+    binder.update();
+    let groups = figure_groups(&backend);
+    // 閉じるボタンは一番右に並ぶ
+    assert_eq!(backend.children(groups[1]).len(), 13);
+    click(&mut binder, 585.0, 115.0);
+    assert_eq!(figure_groups(&backend), vec![groups[0]]);
+    assert_eq!(*closed.borrow(), vec![figure_id]);
+}
+
+#[test]
+fn removes_figure_by_id() {
+    let (mut binder, backend) = new_empty_binder();
+    let first = binder.add_figure(&FigureBuilder::new().show_content_button(false));
+    binder.add_figure(
+        &FigureBuilder::new()
+            .position(150.0, 100.0)
+            .show_content_button(false),
+    );
+    binder.update();
+    let groups = figure_groups(&backend);
+    assert!(binder.remove_figure(first));
+    assert!(!binder.remove_figure(first));
+    binder.update();
+    assert_eq!(figure_groups(&backend), vec![groups[1]]);
+    // 残った Figure はこれまで通りつかめる
+    drag(&mut binder, (200.0, 110.0), (200.0, 210.0));
+    assert_eq!(
+        backend.get_attribute(groups[1], "transform").unwrap(),
+        "translate(150, 200)"
+    );
+}
+
+#[test]
+fn adds_figure_after_remove() {
+    let (mut binder, backend) = new_empty_binder();
+    let first = binder.add_figure(&FigureBuilder::new().show_content_button(false));
+    binder.add_figure(
+        &FigureBuilder::new()
+            .position(400.0, 100.0)
+            .show_content_button(false),
+    );
+    binder.remove_figure(first);
+    let third = binder.add_figure(
+        &FigureBuilder::new()
+            .position(100.0, 400.0)
+            .show_content_button(false),
+    );
+    binder.update();
+    assert_ne!(first, third);
+    let groups = figure_groups(&backend);
+    assert_eq!(groups.len(), 2);
+    drag(&mut binder, (150.0, 410.0), (150.0, 510.0));
+    assert_eq!(
+        backend.get_attribute(groups[1], "transform").unwrap(),
+        "translate(100, 500)"
+    );
+}
+
+fn add_resizable_figure(binder: &mut Binder) {
+    binder.add_figure(
+        &FigureBuilder::new()
+            .width(300.0, 180.0, f64::INFINITY)
+            .height(200.0, 30.0, f64::INFINITY)
+            .maximize_button(true)
+            .show_content_button(false),
+    );
+    binder.update();
+}
+
+fn original_geometry() -> (String, String, String) {
+    (
+        "translate(100, 100)".to_string(),
+        "300".to_string(),
+        "200".to_string(),
+    )
+}
+
+#[test]
+fn maximizes_and_restores_figure() {
+    let (mut binder, backend) = new_empty_binder();
+    add_resizable_figure(&mut binder);
+    click(&mut binder, 385.0, 115.0);
+    assert_eq!(
+        geometry(&backend),
+        (
+            "translate(0, 0)".to_string(),
+            "1200".to_string(),
+            "800".to_string()
+        )
+    );
+    // 最大化した後は右上に移動したボタンで元に戻す
+    click(&mut binder, 1185.0, 15.0);
+    assert_eq!(geometry(&backend), original_geometry());
+}
+
+#[test]
+fn minimizes_and_restores_figure() {
+    let (mut binder, backend) = new_empty_binder();
+    add_resizable_figure(&mut binder);
+    click(&mut binder, 360.0, 115.0);
+    assert_eq!(
+        geometry(&backend),
+        (
+            "translate(100, 100)".to_string(),
+            "180".to_string(),
+            "30".to_string()
+        )
+    );
+    click(&mut binder, 240.0, 115.0);
+    assert_eq!(geometry(&backend), original_geometry());
+}
+
+#[test]
+fn restores_size_at_position_moved_while_minimized() {
+    let (mut binder, backend) = new_empty_binder();
+    add_resizable_figure(&mut binder);
+    click(&mut binder, 360.0, 115.0);
+    drag(&mut binder, (150.0, 110.0), (650.0, 410.0));
+    click(&mut binder, 740.0, 415.0);
+    assert_eq!(
+        geometry(&backend),
+        (
+            "translate(600, 400)".to_string(),
+            "300".to_string(),
+            "200".to_string()
+        )
+    );
+    // 右下の隅で戻した時は container に収まる位置に動かす
+    click(&mut binder, 860.0, 415.0);
+    drag(&mut binder, (650.0, 410.0), (2000.0, 2000.0));
+    click(&mut binder, 1160.0, 785.0);
+    assert_eq!(
+        geometry(&backend),
+        (
+            "translate(900, 600)".to_string(),
+            "300".to_string(),
+            "200".to_string()
+        )
+    );
+}
+
+#[test]
+fn restores_geometry_before_minimize_from_maximized() {
+    let (mut binder, backend) = new_empty_binder();
+    add_resizable_figure(&mut binder);
+    click(&mut binder, 360.0, 115.0);
+    click(&mut binder, 265.0, 115.0);
+    assert_eq!(geometry(&backend).1, "1200");
+    click(&mut binder, 1185.0, 15.0);
+    assert_eq!(geometry(&backend), original_geometry());
+}
+
+fn size(backend: &MemoryBackend) -> (String, String) {
+    let (_, width, height) = geometry(backend);
+    (width, height)
+}
+
+fn size_of(width: &str, height: &str) -> (String, String) {
+    (width.to_string(), height.to_string())
+}
+
+#[test]
+fn stops_resizing_at_max_length() {
+    let (mut binder, backend) = new_empty_binder();
+    binder.add_figure(
+        &FigureBuilder::new()
+            .width(300.0, 180.0, 400.0)
+            .height(200.0, 30.0, 250.0)
+            .show_content_button(false),
+    );
+    binder.update();
+    drag(&mut binder, (398.0, 298.0), (600.0, 500.0));
+    assert_eq!(size(&backend), size_of("400", "250"));
+    // 上限で止まった後に縮めると、止まった位置から縮む
+    drag(&mut binder, (498.0, 348.0), (448.0, 298.0));
+    assert_eq!(size(&backend), size_of("350", "200"));
+}
+
+#[test]
+fn keeps_aspect_ratio_while_resizing() {
+    let (mut binder, backend) = new_empty_binder();
+    binder.add_figure(
+        &FigureBuilder::new()
+            .width(300.0, 150.0, f64::INFINITY)
+            .height(200.0, 30.0, 300.0)
+            .aspect_ratio(1.5)
+            .show_content_button(false),
+    );
+    binder.update();
+    drag(&mut binder, (398.0, 298.0), (458.0, 308.0));
+    assert_eq!(size(&backend), size_of("360", "240"));
+    // height の上限に合わせて width も止まる
+    drag(&mut binder, (458.0, 338.0), (758.0, 338.0));
+    assert_eq!(size(&backend), size_of("450", "300"));
+}
+
+#[test]
+fn keeps_aspect_ratio_inside_container() {
+    let (mut binder, backend) = new_empty_binder();
+    for (x, y) in [(60.0, 100.0), (700.0, 500.0)].iter() {
+        binder.add_figure(
+            &FigureBuilder::new()
+                .position(*x, *y)
+                .width(300.0, 150.0, f64::INFINITY)
+                .height(200.0, 30.0, f64::INFINITY)
+                .aspect_ratio(1.5)
+                .show_content_button(false),
+        );
+    }
+    binder.update();
+    // つかんだウィンドウは最前面の g になる
+    let front_geometry = |backend: &MemoryBackend| {
+        let group = figure_groups(backend)[1];
+        let base_rect = backend.children(group)[0];
+        vec![
+            backend.get_attribute(group, "transform").unwrap(),
+            backend.get_attribute(base_rect, "width").unwrap(),
+            backend.get_attribute(base_rect, "height").unwrap(),
+        ]
+    };
+    // 左上の角は左端で止まり、上の辺は比率に合わせて動く
+    drag(&mut binder, (61.0, 101.0), (-200.0, 101.0));
+    assert_eq!(
+        front_geometry(&backend),
+        vec!["translate(0, 60)", "360", "240"]
+    );
+    // 右の辺を広げても、比率で伸びる下の辺は container の下端で止まる
+    drag(&mut binder, (998.0, 600.0), (1198.0, 600.0));
+    assert_eq!(
+        front_geometry(&backend),
+        vec!["translate(700, 500)", "450", "300"]
+    );
+}
+
+#[test]
+fn resets_size_to_default() {
+    let (mut binder, backend) = new_empty_binder();
+    let figure_id = binder.add_figure(
+        &FigureBuilder::new()
+            .width(300.0, 180.0, f64::INFINITY)
+            .height(200.0, 30.0, f64::INFINITY)
+            .show_content_button(false),
+    );
+    binder.update();
+    drag(&mut binder, (398.0, 298.0), (498.0, 398.0));
+    assert_eq!(size(&backend), size_of("400", "300"));
+    assert!(binder.reset_size(figure_id));
+    binder.update();
+    assert_eq!(size(&backend), size_of("300", "200"));
+}
@@ -0,0 +1,178 @@
+//! ログの末尾への追従と未読の表示の確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{click, content_offset, figure_group};
+use std::cell::RefCell;
+use std::rc::Rc;
+use window_lib::binder::{Binder, TableContent};
+use window_lib::figure_builder::{ContentKind, FigureBuilder};
+use window_lib::render_backend::{MemoryBackend, RenderBackend};
+
+// 行を後から追加できる内容
+struct SharedLog {
+    lines: Rc<RefCell<Vec<Vec<String>>>>,
+}
+
+impl TableContent for SharedLog {
+    fn get_tbody(&self, _key: &str) -> Vec<Vec<String>> {
+        self.lines.borrow().clone()
+    }
+}
+
+// 末尾へ移動するボタンの中心
+const JUMP_BUTTON: (f64, f64) = (376.0, 206.0);
+
+// 100, 100 に表示領域の高さ 95 のログウィンドウを置く
+fn new_binder(
+    follow_tail: bool,
+    line_count: usize,
+) -> (Binder, MemoryBackend, Rc<RefCell<Vec<Vec<String>>>>) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    let lines = Rc::new(RefCell::new(vec![]));
+    binder.set_table_content_state(Box::new(SharedLog {
+        lines: lines.clone(),
+    }));
+    push_lines(&lines, line_count);
+    binder.add_figure(
+        &FigureBuilder::new()
+            .width(300.0, 180.0, f64::INFINITY)
+            .height(130.0, 30.0, f64::INFINITY)
+            .content_kind(ContentKind::Log)
+            .show_content_button(false)
+            .follow_tail(follow_tail),
+    );
+    binder.update();
+    (binder, backend, lines)
+}
+
+fn push_lines(lines: &Rc<RefCell<Vec<Vec<String>>>>, count: usize) {
+    let mut lines = lines.borrow_mut();
+    for _ in 0..count {
+        let line = format!("log {}", lines.len());
+        lines.push(vec![line]);
+    }
+}
+
+// 最後の子が未読の行数、その前がボタンの g
+fn unread_indicator(backend: &MemoryBackend) -> (String, Option<String>) {
+    let children = backend.children(figure_group(backend));
+    let count_text = children[children.len() - 1];
+    let button_group = children[children.len() - 2];
+    (
+        backend.text(count_text),
+        backend.get_attribute(button_group, "display"),
+    )
+}
+
+#[test]
+fn appended_lines_keep_view_at_end() {
+    let (mut binder, backend, lines) = new_binder(true, 2);
+    assert_eq!(content_offset(&backend).1, 30.0);
+    push_lines(&lines, 10);
+    binder.invalidate();
+    binder.update();
+    assert!(content_offset(&backend).1 < 30.0);
+    // すでに末尾にいる
+    assert!(!binder.scroll_to(0, f64::NAN, f64::INFINITY));
+    assert_eq!(binder.unread_lines(0), 0);
+    let bottom = content_offset(&backend).1;
+    push_lines(&lines, 1);
+    binder.invalidate();
+    binder.update();
+    assert_eq!(content_offset(&backend).1, bottom - 25.0);
+    assert_eq!(
+        unread_indicator(&backend),
+        ("".to_string(), Some("none".to_string()))
+    );
+}
+
+#[test]
+fn scrolled_up_view_keeps_position_and_counts_unread() {
+    let (mut binder, backend, lines) = new_binder(true, 10);
+    assert!(binder.scroll_to(0, f64::NAN, 20.0));
+    binder.update();
+    assert_eq!(content_offset(&backend).1, 10.0);
+    push_lines(&lines, 3);
+    binder.invalidate();
+    binder.update();
+    assert_eq!(content_offset(&backend).1, 10.0);
+    assert_eq!(binder.unread_lines(0), 3);
+    assert_eq!(unread_indicator(&backend), ("3".to_string(), None));
+    push_lines(&lines, 2);
+    binder.invalidate();
+    binder.update();
+    assert_eq!(content_offset(&backend).1, 10.0);
+    assert_eq!(binder.unread_lines(0), 5);
+}
+
+#[test]
+fn jump_to_end_button_scrolls_to_newest_line() {
+    let (mut binder, backend, lines) = new_binder(true, 10);
+    assert!(binder.scroll_to(0, f64::NAN, 0.0));
+    push_lines(&lines, 3);
+    binder.invalidate();
+    binder.update();
+    assert_eq!(binder.unread_lines(0), 3);
+    click(&mut binder, JUMP_BUTTON.0, JUMP_BUTTON.1);
+    assert!(!binder.scroll_to(0, f64::NAN, f64::INFINITY));
+    assert_eq!(binder.unread_lines(0), 0);
+    assert_eq!(
+        unread_indicator(&backend),
+        ("".to_string(), Some("none".to_string()))
+    );
+}
+
+#[test]
+fn dragging_thumb_past_either_end_stops_at_that_end() {
+    let (mut binder, backend, lines) = new_binder(true, 10);
+    assert!(binder.scroll_to(0, f64::NAN, 0.0));
+    binder.update();
+    // 縦のスクロールバーのつまみを下端より先まで引くと、末尾に追従する
+    binder.mouse_down(390.0, 132.0);
+    binder.mouse_move(390.0, 700.0);
+    binder.mouse_up(390.0, 700.0);
+    binder.update();
+    assert!(!binder.scroll_to(0, f64::NAN, f64::INFINITY));
+    let bottom = content_offset(&backend).1;
+    push_lines(&lines, 1);
+    binder.invalidate();
+    binder.update();
+    assert_eq!(content_offset(&backend).1, bottom - 25.0);
+    assert_eq!(binder.unread_lines(0), 0);
+    // 上端より先まで戻すと先頭で止まり、追加した行は未読になる
+    binder.mouse_down(390.0, 220.0);
+    binder.mouse_move(390.0, 0.0);
+    binder.mouse_up(390.0, 0.0);
+    binder.update();
+    assert_eq!(content_offset(&backend).1, 30.0);
+    push_lines(&lines, 2);
+    binder.invalidate();
+    binder.update();
+    assert_eq!(content_offset(&backend).1, 30.0);
+    assert_eq!(binder.unread_lines(0), 2);
+}
+
+#[test]
+fn hidden_jump_to_end_button_is_ignored() {
+    let (mut binder, backend, _) = new_binder(true, 10);
+    // 遡っただけでは未読がないのでボタンは出ない
+    assert!(binder.scroll_to(0, f64::NAN, 0.0));
+    binder.update();
+    assert_eq!(binder.unread_lines(0), 0);
+    click(&mut binder, JUMP_BUTTON.0, JUMP_BUTTON.1);
+    assert_eq!(content_offset(&backend).1, 30.0);
+}
+
+#[test]
+fn without_follow_tail_appended_lines_stay_below() {
+    let (mut binder, backend, lines) = new_binder(false, 2);
+    push_lines(&lines, 10);
+    binder.invalidate();
+    binder.update();
+    assert_eq!(content_offset(&backend).1, 30.0);
+    assert_eq!(binder.unread_lines(0), 0);
+}
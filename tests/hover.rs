@@ -0,0 +1,152 @@
+//! mouse_move でのホバー状態の確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::figure_group;
+use std::cell::RefCell;
+use std::rc::Rc;
+use window_lib::binder::Binder;
+use window_lib::figure_builder::FigureBuilder;
+use window_lib::render_backend::{MemoryBackend, RenderBackend};
+
+type Events = Rc<RefCell<Vec<String>>>;
+
+// 100, 100 に 300x200 の Figure を置き、enter / leave を文字列で記録する
+fn new_binder() -> (Binder, MemoryBackend, usize, Events) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    let events: Events = Rc::new(RefCell::new(vec![]));
+    let enter_events = events.clone();
+    binder.set_on_hover_enter_handler(Box::new(move |figure_id, name| {
+        enter_events
+            .borrow_mut()
+            .push(format!("enter {} {}", figure_id, name))
+    }));
+    let leave_events = events.clone();
+    binder.set_on_hover_leave_handler(Box::new(move |figure_id, name| {
+        leave_events
+            .borrow_mut()
+            .push(format!("leave {} {}", figure_id, name))
+    }));
+    let figure_id = binder.add_figure(
+        &FigureBuilder::new()
+            .width(300.0, 180.0, f64::INFINITY)
+            .height(200.0, 30.0, f64::INFINITY)
+            .show_content_button(false)
+            .close_button(true),
+    );
+    binder.update();
+    (binder, backend, figure_id, events)
+}
+
+fn container_cursor(backend: &MemoryBackend) -> Option<String> {
+    backend.get_attribute(backend.container(), "style")
+}
+
+#[test]
+fn calls_enter_and_leave_callbacks() {
+    let (mut binder, _, figure_id, events) = new_binder();
+    binder.mouse_move(200.0, 115.0);
+    binder.mouse_move(210.0, 115.0);
+    binder.mouse_move(385.0, 115.0);
+    binder.mouse_move(600.0, 115.0);
+    assert_eq!(
+        *events.borrow(),
+        vec![
+            format!("enter {} title", figure_id),
+            format!("leave {} title", figure_id),
+            format!("enter {} close-button", figure_id),
+            format!("leave {} close-button", figure_id),
+        ]
+    );
+}
+
+#[test]
+fn highlights_hovered_button() {
+    let (mut binder, backend, _, _) = new_binder();
+    let group = figure_group(&backend);
+    // 閉じるボタンの rect と symbol を複製した g
+    let close_rect = backend.children(group)[10];
+    let close_group = backend.children(group)[11];
+    binder.mouse_move(385.0, 115.0);
+    assert_eq!(
+        backend.get_attribute(close_rect, "class").as_deref(),
+        Some("hover")
+    );
+    assert_eq!(
+        backend.get_attribute(close_group, "class").as_deref(),
+        Some("hover")
+    );
+    assert_eq!(
+        container_cursor(&backend).as_deref(),
+        Some("cursor: pointer;")
+    );
+    binder.mouse_move(600.0, 115.0);
+    assert_eq!(backend.get_attribute(close_rect, "class"), None);
+    assert_eq!(container_cursor(&backend), None);
+}
+
+#[test]
+fn keeps_other_classes_while_hovering() {
+    let (mut binder, mut backend, _, _) = new_binder();
+    let close_rect = backend.children(figure_group(&backend))[10];
+    backend.set_attribute(close_rect, "class", "button  hover-able");
+    binder.mouse_move(385.0, 115.0);
+    assert_eq!(
+        backend.get_attribute(close_rect, "class").as_deref(),
+        Some("button hover-able hover")
+    );
+    binder.mouse_move(600.0, 115.0);
+    assert_eq!(
+        backend.get_attribute(close_rect, "class").as_deref(),
+        Some("button hover-able")
+    );
+}
+
+#[test]
+fn shows_resize_cursor_on_edges() {
+    let (mut binder, backend, figure_id, events) = new_binder();
+    binder.mouse_move(398.0, 200.0);
+    assert_eq!(
+        container_cursor(&backend).as_deref(),
+        Some("cursor: ew-resize;")
+    );
+    binder.mouse_move(398.0, 298.0);
+    assert_eq!(
+        container_cursor(&backend).as_deref(),
+        Some("cursor: nwse-resize;")
+    );
+    assert_eq!(
+        events.borrow().last(),
+        Some(&format!("enter {} resize-se", figure_id))
+    );
+}
+
+#[test]
+fn keeps_hover_while_dragging() {
+    let (mut binder, backend, figure_id, events) = new_binder();
+    binder.mouse_move(200.0, 115.0);
+    binder.mouse_down(200.0, 115.0);
+    binder.mouse_move(700.0, 115.0);
+    assert_eq!(events.borrow().len(), 1);
+    assert_eq!(container_cursor(&backend).as_deref(), Some("cursor: grab;"));
+    // 離した時点の位置で改めて判定する（タイトルはウィンドウと一緒に動いている）
+    binder.mouse_up(700.0, 115.0);
+    assert_eq!(events.borrow().len(), 1);
+    assert_eq!(events.borrow()[0], format!("enter {} title", figure_id));
+}
+
+#[test]
+fn leaves_closed_figure() {
+    let (mut binder, backend, figure_id, events) = new_binder();
+    binder.mouse_move(385.0, 115.0);
+    binder.mouse_down(385.0, 115.0);
+    binder.mouse_up(385.0, 115.0);
+    assert_eq!(
+        events.borrow().last(),
+        Some(&format!("leave {} close-button", figure_id))
+    );
+    assert_eq!(container_cursor(&backend), None);
+}
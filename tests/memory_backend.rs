@@ -0,0 +1,136 @@
+//! Binder のマウス操作を MemoryBackend 上で確認するテスト
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{click, figure_groups, new_dev_binder};
+use window_lib::render_backend::{MemoryBackend, NodeId, RenderBackend};
+
+fn base_rect(backend: &MemoryBackend, group: NodeId) -> NodeId {
+    backend.children(group)[0]
+}
+
+#[test]
+fn creates_dev_windows() {
+    let (_, backend) = new_dev_binder();
+    let groups = figure_groups(&backend);
+    assert_eq!(groups.len(), 3);
+    assert_eq!(
+        backend.get_attribute(groups[0], "transform").unwrap(),
+        "translate(100, 100)"
+    );
+    let title = backend.children(groups[0])[7];
+    assert_eq!(backend.tag_name(title), "text");
+    assert_eq!(backend.text(title), "プレイヤー1");
+}
+
+#[test]
+fn drags_window_by_title() {
+    let (mut binder, backend) = new_dev_binder();
+    let group = figure_groups(&backend)[0];
+    binder.mouse_down(150.0, 110.0);
+    binder.mouse_move(250.0, 160.0);
+    binder.mouse_up(250.0, 160.0);
+    binder.update();
+    assert_eq!(
+        backend.get_attribute(group, "transform").unwrap(),
+        "translate(200, 150)"
+    );
+}
+
+#[test]
+fn drag_stops_at_container_origin() {
+    let (mut binder, backend) = new_dev_binder();
+    let group = figure_groups(&backend)[0];
+    binder.mouse_down(150.0, 110.0);
+    binder.mouse_move(0.0, 0.0);
+    binder.mouse_up(0.0, 0.0);
+    binder.update();
+    assert_eq!(
+        backend.get_attribute(group, "transform").unwrap(),
+        "translate(0, 0)"
+    );
+}
+
+#[test]
+fn grabbed_window_moves_to_front() {
+    let (mut binder, backend) = new_dev_binder();
+    let first = figure_groups(&backend)[0];
+    click(&mut binder, 150.0, 110.0);
+    assert_eq!(figure_groups(&backend).last(), Some(&first));
+}
+
+#[test]
+fn resizes_window_from_bottom_frame() {
+    let (mut binder, backend) = new_dev_binder();
+    let group = figure_groups(&backend)[1];
+    let rect = base_rect(&backend, group);
+    let height: f64 = backend
+        .get_attribute(rect, "height")
+        .unwrap()
+        .parse()
+        .unwrap();
+    let bottom = 100.0 + height - 2.0;
+    binder.mouse_down(400.0, bottom);
+    binder.mouse_move(400.0, bottom + 40.0);
+    binder.mouse_up(400.0, bottom + 40.0);
+    binder.update();
+    assert_eq!(
+        backend.get_attribute(rect, "height").unwrap(),
+        (height + 40.0).to_string()
+    );
+}
+
+#[test]
+fn minimize_button_shrinks_window() {
+    let (mut binder, backend) = new_dev_binder();
+    let group = figure_groups(&backend)[1];
+    let rect = base_rect(&backend, group);
+    let width: f64 = backend
+        .get_attribute(rect, "width")
+        .unwrap()
+        .parse()
+        .unwrap();
+    // 最小化ボタンは右上から二つ目
+    let button_x = 350.0 + width - 5.0 - 20.0 - 5.0 - 10.0;
+    click(&mut binder, button_x, 115.0);
+    assert_eq!(backend.get_attribute(rect, "width").unwrap(), "180");
+    assert_eq!(backend.get_attribute(rect, "height").unwrap(), "30");
+}
+
+#[test]
+fn reads_comments_and_quoted_attributes_in_index_html() {
+    let backend = MemoryBackend::new_with_index_html(
+        r#"<svg id="container" opacity="0"><defs>
+            <!-- <rect id="old"> -->
+            <rect id='base' data-label="a > b" hidden/>
+            <symbol id="close"><path d="M 0 0"></path></symbol>
+        </defs></svg>"#,
+    )
+    .unwrap();
+    let container = backend.container();
+    assert_eq!(backend.get_attribute(container, "opacity"), None);
+    assert_eq!(backend.find_by_id("old"), None);
+    let base = backend.find_by_id("base").unwrap();
+    assert_eq!(
+        backend.get_attribute(base, "data-label").as_deref(),
+        Some("a > b")
+    );
+    assert_eq!(backend.get_attribute(base, "hidden").as_deref(), Some(""));
+    let close = backend.find_by_id("close").unwrap();
+    assert_eq!(backend.tag_name(backend.children(close)[0]), "path");
+}
+
+#[test]
+fn names_markup_that_cannot_be_read() {
+    let error = MemoryBackend::new_with_index_html(r#"<svg id="c"><defs><rect id="a></defs>"#)
+        .err()
+        .unwrap();
+    assert!(error.contains(r#"<rect id="a"#), "{}", error);
+    let error = MemoryBackend::new_with_index_html(r#"<svg id="c"><defs><g></symbol></defs>"#)
+        .err()
+        .unwrap();
+    assert!(error.contains("</symbol>"), "{}", error);
+    assert!(MemoryBackend::new_with_index_html("<html></html>").is_err());
+}
@@ -0,0 +1,121 @@
+//! pointer_* によるタッチと複数ポインターの確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::figure_groups;
+use std::cell::RefCell;
+use std::rc::Rc;
+use window_lib::binder::Binder;
+use window_lib::figure_builder::FigureBuilder;
+use window_lib::render_backend::{MemoryBackend, NodeId, RenderBackend};
+
+// 100, 100 と 500, 100 に 300x200 の Figure を置く
+fn new_binder() -> (Binder, MemoryBackend) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    for x in [100.0, 500.0] {
+        binder.add_figure(
+            &FigureBuilder::new()
+                .position(x, 100.0)
+                .width(300.0, 180.0, f64::INFINITY)
+                .height(200.0, 30.0, f64::INFINITY)
+                .show_content_button(false)
+                .close_button(true),
+        );
+    }
+    binder.update();
+    (binder, backend)
+}
+
+fn transform(backend: &MemoryBackend, group: NodeId) -> String {
+    backend.get_attribute(group, "transform").unwrap()
+}
+
+fn touch_down(binder: &mut Binder, pointer_id: i32, x: f64, y: f64, is_primary: bool) {
+    binder.pointer_down(pointer_id, "touch", x, y, 0.5, is_primary);
+}
+
+fn touch_move(binder: &mut Binder, pointer_id: i32, x: f64, y: f64, is_primary: bool) {
+    binder.pointer_move(pointer_id, "touch", x, y, 0.5, is_primary);
+}
+
+#[test]
+fn drags_two_figures_with_two_fingers() {
+    let (mut binder, backend) = new_binder();
+    let groups = figure_groups(&backend);
+    touch_down(&mut binder, 10, 150.0, 110.0, true);
+    touch_down(&mut binder, 11, 550.0, 110.0, false);
+    touch_move(&mut binder, 10, 150.0, 310.0, true);
+    touch_move(&mut binder, 11, 600.0, 410.0, false);
+    binder.pointer_up(10, 150.0, 310.0);
+    binder.pointer_up(11, 600.0, 410.0);
+    binder.update();
+    assert_eq!(transform(&backend, groups[0]), "translate(100, 300)");
+    assert_eq!(transform(&backend, groups[1]), "translate(550, 400)");
+}
+
+#[test]
+fn ignores_second_finger_on_grabbed_figure() {
+    let (mut binder, backend) = new_binder();
+    let groups = figure_groups(&backend);
+    touch_down(&mut binder, 10, 150.0, 110.0, true);
+    touch_down(&mut binder, 11, 200.0, 110.0, false);
+    touch_move(&mut binder, 11, 200.0, 410.0, false);
+    touch_move(&mut binder, 10, 170.0, 130.0, true);
+    binder.pointer_up(11, 200.0, 410.0);
+    binder.pointer_up(10, 170.0, 130.0);
+    binder.update();
+    assert_eq!(transform(&backend, groups[0]), "translate(120, 120)");
+}
+
+#[test]
+fn closes_figure_by_tap() {
+    let (mut binder, backend) = new_binder();
+    let groups = figure_groups(&backend);
+    let closed = Rc::new(RefCell::new(vec![]));
+    let closed_in_handler = closed.clone();
+    binder.set_on_close_handler(Box::new(move |figure_id| {
+        closed_in_handler.borrow_mut().push(figure_id)
+    }));
+    touch_down(&mut binder, 10, 785.0, 115.0, true);
+    binder.pointer_up(10, 785.0, 115.0);
+    binder.update();
+    assert_eq!(figure_groups(&backend), vec![groups[0]]);
+    assert_eq!(closed.borrow().len(), 1);
+}
+
+#[test]
+fn cancels_without_pressing_button() {
+    let (mut binder, backend) = new_binder();
+    touch_down(&mut binder, 10, 785.0, 115.0, true);
+    binder.pointer_cancel(10);
+    binder.pointer_up(10, 785.0, 115.0);
+    binder.update();
+    assert_eq!(figure_groups(&backend).len(), 2);
+}
+
+#[test]
+fn does_not_hover_by_touch() {
+    let (mut binder, backend) = new_binder();
+    touch_move(&mut binder, 10, 785.0, 115.0, true);
+    assert_eq!(backend.get_attribute(backend.container(), "style"), None);
+    binder.pointer_move(2, "pen", 785.0, 115.0, 0.0, true);
+    assert_eq!(
+        backend
+            .get_attribute(backend.container(), "style")
+            .as_deref(),
+        Some("cursor: pointer;")
+    );
+}
+
+#[test]
+fn keeps_pressure_while_pressed() {
+    let (mut binder, _) = new_binder();
+    binder.pointer_down(2, "pen", 150.0, 110.0, 0.3, true);
+    binder.pointer_move(2, "pen", 160.0, 110.0, 0.7, true);
+    assert_eq!(binder.pointer_pressure(2), Some(0.7));
+    binder.pointer_up(2, 160.0, 110.0);
+    assert_eq!(binder.pointer_pressure(2), None);
+}
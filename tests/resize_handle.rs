@@ -0,0 +1,88 @@
+//! 辺と角のサイズ変更領域の確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{drag, figure_group, geometry, new_binder, new_binder_with};
+use window_lib::figure_builder::FigureBuilder;
+use window_lib::render_backend::RenderBackend;
+
+fn geometry_of(x: i32, y: i32, width: i32, height: i32) -> (String, String, String) {
+    (
+        format!("translate({}, {})", x, y),
+        width.to_string(),
+        height.to_string(),
+    )
+}
+
+#[test]
+fn resizes_only_width_from_east_edge() {
+    let (mut binder, backend) = new_binder();
+    drag(&mut binder, (398.0, 200.0), (448.0, 250.0));
+    assert_eq!(geometry(&backend), geometry_of(100, 100, 350, 200));
+}
+
+#[test]
+fn resizes_only_height_from_north_edge() {
+    let (mut binder, backend) = new_binder();
+    drag(&mut binder, (250.0, 102.0), (200.0, 82.0));
+    assert_eq!(geometry(&backend), geometry_of(100, 80, 300, 220));
+}
+
+#[test]
+fn resizes_both_axes_from_north_west_corner() {
+    let (mut binder, backend) = new_binder();
+    drag(&mut binder, (102.0, 102.0), (82.0, 72.0));
+    assert_eq!(geometry(&backend), geometry_of(80, 70, 320, 230));
+}
+
+#[test]
+fn treats_edge_near_corner_as_corner() {
+    let (mut binder, backend) = new_binder();
+    // 上の辺でも右端に近い所は NE として扱う
+    drag(&mut binder, (390.0, 102.0), (410.0, 92.0));
+    assert_eq!(geometry(&backend), geometry_of(100, 90, 320, 210));
+}
+
+#[test]
+fn widens_handle_by_thickness() {
+    let (mut binder, backend) = new_binder_with(FigureBuilder::new().resize_handle_thickness(12.0));
+    // 既定の太さではスクロールバーの位置だが、太くした handle が優先される
+    drag(&mut binder, (390.0, 200.0), (410.0, 200.0));
+    assert_eq!(geometry(&backend), geometry_of(100, 100, 320, 200));
+}
+
+#[test]
+fn sets_cursor_for_each_handle() {
+    let (_, backend) = new_binder();
+    let group = figure_group(&backend);
+    let handle_group = *backend.children(group).last().unwrap();
+    let cursors: Vec<String> = backend
+        .children(handle_group)
+        .into_iter()
+        .map(|rect| backend.get_attribute(rect, "style").unwrap())
+        .collect();
+    assert_eq!(
+        cursors,
+        vec![
+            "cursor: ns-resize;",
+            "cursor: ns-resize;",
+            "cursor: ew-resize;",
+            "cursor: ew-resize;",
+            "cursor: nesw-resize;",
+            "cursor: nesw-resize;",
+            "cursor: nwse-resize;",
+            "cursor: nwse-resize;",
+            "cursor: nwse-resize;",
+            "cursor: nwse-resize;",
+            "cursor: nesw-resize;",
+            "cursor: nesw-resize;",
+        ]
+    );
+    // E の辺は右端に沿って、角を除いた長さで置く
+    let east = backend.children(handle_group)[2];
+    assert_eq!(backend.get_attribute(east, "x").unwrap(), "295");
+    assert_eq!(backend.get_attribute(east, "y").unwrap(), "15");
+    assert_eq!(backend.get_attribute(east, "height").unwrap(), "170");
+}
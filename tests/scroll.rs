@@ -0,0 +1,143 @@
+//! スクロールバーの溝とスクロール API の確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::content_offset;
+use window_lib::binder::Binder;
+use window_lib::figure_builder::{FigureBuilder, TextAnchorType};
+use window_lib::render_backend::MemoryBackend;
+
+// 100, 100 に内容が縦にはみ出す小さな Figure を置く。幅が 230 未満なら横にもはみ出す
+// 値の列は 205 で右に揃える
+fn new_binder(width: f64) -> (Binder, MemoryBackend) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    binder.set_dummy_state();
+    binder.add_figure(
+        &FigureBuilder::new()
+            .width(width, 100.0, f64::INFINITY)
+            .height(60.0, 30.0, f64::INFINITY)
+            .show_content_button(false)
+            .fixed_column(TextAnchorType::Start, 100.0)
+            .fixed_column(TextAnchorType::End, 90.0),
+    );
+    binder.update();
+    (binder, backend)
+}
+
+// 幅 240 の時の縦のスクロールバーの溝の x
+const TRACK_X: f64 = 330.0;
+
+fn update_frames(binder: &mut Binder, frames: usize) {
+    for _ in 0..frames {
+        binder.update();
+    }
+}
+
+#[test]
+fn track_click_pages_by_viewport() {
+    let (mut binder, backend) = new_binder(240.0);
+    assert_eq!(content_offset(&backend), (5.0, 30.0));
+    // つまみより下を押すと表示領域の高さ 25 だけ進む
+    binder.mouse_down(TRACK_X, 154.0);
+    binder.mouse_up(TRACK_X, 154.0);
+    binder.update();
+    assert_eq!(content_offset(&backend), (5.0, 5.0));
+    // つまみより上を押すと戻る
+    binder.mouse_down(TRACK_X, 131.0);
+    binder.mouse_up(TRACK_X, 131.0);
+    binder.update();
+    assert_eq!(content_offset(&backend), (5.0, 30.0));
+}
+
+#[test]
+fn holding_track_repeats_until_thumb_reaches_pointer() {
+    let (mut binder, backend) = new_binder(240.0);
+    binder.mouse_down(TRACK_X, 154.0);
+    update_frames(&mut binder, 23);
+    assert_eq!(content_offset(&backend), (5.0, 5.0));
+    update_frames(&mut binder, 1);
+    assert_eq!(content_offset(&backend), (5.0, -20.0));
+    update_frames(&mut binder, 4);
+    assert_eq!(content_offset(&backend), (5.0, -30.0));
+    update_frames(&mut binder, 50);
+    assert_eq!(content_offset(&backend), (5.0, -30.0));
+}
+
+#[test]
+fn releasing_or_leaving_track_stops_paging() {
+    let (mut binder, backend) = new_binder(240.0);
+    binder.mouse_down(TRACK_X, 154.0);
+    binder.mouse_up(TRACK_X, 154.0);
+    update_frames(&mut binder, 50);
+    assert_eq!(content_offset(&backend), (5.0, 5.0));
+
+    let (mut binder, backend) = new_binder(240.0);
+    binder.mouse_down(TRACK_X, 154.0);
+    binder.mouse_move(600.0, 600.0);
+    update_frames(&mut binder, 50);
+    assert_eq!(content_offset(&backend), (5.0, 5.0));
+    binder.mouse_up(600.0, 600.0);
+}
+
+#[test]
+fn track_is_ignored_without_overflow() {
+    let (mut binder, backend) = new_binder(240.0);
+    // 横にははみ出していないので、横のスクロールバーの溝の位置を押しても何もしない
+    binder.mouse_down(200.0, 150.0);
+    binder.mouse_up(200.0, 150.0);
+    binder.update();
+    assert_eq!(content_offset(&backend), (5.0, 30.0));
+}
+
+#[test]
+fn scroll_to_and_scroll_by() {
+    let (mut binder, backend) = new_binder(120.0);
+    // NaN の軸は動かさない
+    assert!(binder.scroll_to(0, 30.0, f64::NAN));
+    binder.update();
+    assert_eq!(content_offset(&backend), (-25.0, 30.0));
+    assert!(binder.scroll_by(0, 0.0, 15.0));
+    binder.update();
+    assert_eq!(content_offset(&backend), (-25.0, 15.0));
+    // 末尾まで送ると、それ以上は動かない
+    assert!(binder.scroll_to(0, f64::INFINITY, f64::INFINITY));
+    binder.update();
+    assert_eq!(content_offset(&backend), (-95.0, -30.0));
+    assert!(!binder.scroll_by(0, 10.0, 10.0));
+    assert!(!binder.scroll_to(1, 0.0, 0.0));
+    assert!(!binder.scroll_by(1, 10.0, 10.0));
+}
+
+#[test]
+fn scroll_to_keeps_state_consistent() {
+    let (mut binder, backend) = new_binder(120.0);
+    assert!(binder.scroll_to(0, f64::NAN, 20.0));
+    // ホイールも、つまみのドラッグも scroll_to した位置から続く
+    assert!(binder.wheel(150.0, 140.0, 0.0, 10.0, 0, false));
+    binder.update();
+    assert_eq!(content_offset(&backend), (5.0, 0.0));
+    // つまみは長さ 25 * 25 / 85 の残りの範囲で、内容のはみ出し 60px 分を動く
+    let thumb_delta = 10.0 * (25.0 - 25.0 * 25.0 / 85.0) / 60.0;
+    binder.mouse_down(210.0, 140.0);
+    binder.mouse_move(210.0, 140.0 + thumb_delta);
+    binder.mouse_up(210.0, 140.0 + thumb_delta);
+    binder.update();
+    assert_eq!(content_offset(&backend), (5.0, -10.0));
+}
+
+#[test]
+fn scrolling_minimized_figure_does_nothing() {
+    let (mut binder, backend) = new_binder(240.0);
+    // 最小化ボタンを押すと内容の表示領域が無くなる
+    binder.mouse_down(325.0, 115.0);
+    binder.mouse_up(325.0, 115.0);
+    binder.update();
+    let offset = content_offset(&backend);
+    assert!(!binder.scroll_by(0, 0.0, 10.0));
+    assert!(!binder.scroll_to(0, 0.0, f64::INFINITY));
+    binder.update();
+    assert_eq!(content_offset(&backend), offset);
+}
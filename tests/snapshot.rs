@@ -0,0 +1,79 @@
+//! Binder::new_for_dev の描画結果をスナップショットと比較するテスト
+//!
+//! 期待値を更新する時は `UPDATE_SNAPSHOTS=1` を付けて実行する
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{click, drag, new_dev_binder};
+use std::path::PathBuf;
+use window_lib::binder::Binder;
+
+fn assert_snapshot(name: &str, actual: &str) {
+    let path: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "tests",
+        "snapshots",
+        format!("{}.svg", name).as_str(),
+    ]
+    .iter()
+    .collect();
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "{} がありません。UPDATE_SNAPSHOTS=1 を付けて実行してください",
+            path.display()
+        )
+    });
+    assert_eq!(actual, expected, "{} と一致しません", path.display());
+}
+
+// 各ウィンドウの右上のボタンで内容に合わせたサイズにする
+fn show_all_contents(binder: &mut Binder) {
+    click(binder, 265.0, 115.0);
+    click(binder, 515.0, 115.0);
+    click(binder, 1085.0, 665.0);
+}
+
+#[test]
+fn dev_initial() {
+    let (_, backend) = new_dev_binder();
+    assert_snapshot("dev_initial", &backend.to_svg_string());
+}
+
+#[test]
+fn dev_show_content() {
+    let (mut binder, backend) = new_dev_binder();
+    show_all_contents(&mut binder);
+    assert_snapshot("dev_show_content", &backend.to_svg_string());
+}
+
+#[test]
+fn dev_drag_title() {
+    let (mut binder, backend) = new_dev_binder();
+    show_all_contents(&mut binder);
+    drag(&mut binder, (150.0, 110.0), (230.0, 420.0));
+    assert_snapshot("dev_drag_title", &backend.to_svg_string());
+}
+
+#[test]
+fn dev_resize_from_left_frame() {
+    let (mut binder, backend) = new_dev_binder();
+    show_all_contents(&mut binder);
+    drag(&mut binder, (352.0, 150.0), (300.0, 150.0));
+    assert_snapshot("dev_resize_from_left_frame", &backend.to_svg_string());
+}
+
+#[test]
+fn dev_scroll_after_shrink() {
+    let (mut binder, backend) = new_dev_binder();
+    show_all_contents(&mut binder);
+    // 右端を縮めて横スクロールバーを出してから、スクロールバーを右へ動かす
+    drag(&mut binder, (576.0, 150.0), (500.0, 150.0));
+    drag(&mut binder, (400.0, 215.0), (420.0, 215.0));
+    assert_snapshot("dev_scroll_after_shrink", &backend.to_svg_string());
+}
@@ -0,0 +1,103 @@
+//! Binder::set_state_value で保存した値を表の {path} に埋め込む確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use window_lib::binder::{Binder, TableContent};
+use window_lib::figure_builder::FigureBuilder;
+use window_lib::render_backend::MemoryBackend;
+use window_lib::state_store::StateValue;
+
+// 値の列に書式を置いた 2 列の表
+struct Template {
+    rows: Vec<(&'static str, &'static str)>,
+}
+
+impl TableContent for Template {
+    fn get_tbody(&self, _key: &str) -> Vec<Vec<String>> {
+        self.rows
+            .iter()
+            .map(|(name, value)| vec![name.to_string(), value.to_string()])
+            .collect()
+    }
+    fn uses_templates(&self) -> bool {
+        true
+    }
+}
+
+fn new_binder(rows: Vec<(&'static str, &'static str)>) -> (Binder, MemoryBackend) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    binder.set_table_content_state(Box::new(Template { rows }));
+    binder.add_figure(&FigureBuilder::new().content_token("status"));
+    binder.update();
+    (binder, backend)
+}
+
+// 値の列の tspan
+fn values(backend: &MemoryBackend) -> Vec<String> {
+    let column = backend.find_by_id("status-tbody-col-1").unwrap();
+    backend
+        .children(column)
+        .into_iter()
+        .map(|tspan| backend.text(tspan))
+        .collect()
+}
+
+fn number(value: f64) -> StateValue {
+    StateValue::Number(value)
+}
+
+fn text(value: &str) -> StateValue {
+    StateValue::Text(value.to_string())
+}
+
+#[test]
+fn cells_follow_state_changes() {
+    let (mut binder, backend) = new_binder(vec![("HP/MHP", "{hp}/{mhp}")]);
+    // 値が無い path は空になる
+    assert_eq!(values(&backend), vec!["/"]);
+    binder.set_state_value("hp", number(50.0));
+    binder.set_state_value("mhp", number(50.0));
+    binder.update();
+    assert_eq!(values(&backend), vec!["50/50"]);
+    let tspan = backend.children(backend.find_by_id("status-tbody-col-1").unwrap())[0];
+    binder.set_state_value("hp", number(12.5));
+    binder.update();
+    assert_eq!(values(&backend), vec!["12.5/50"]);
+    // 要素は作り直さない
+    assert_eq!(
+        backend.children(backend.find_by_id("status-tbody-col-1").unwrap())[0],
+        tspan
+    );
+    assert!(binder.remove_state("hp"));
+    assert!(!binder.remove_state("hp"));
+    binder.update();
+    assert_eq!(values(&backend), vec!["/50"]);
+}
+
+#[test]
+fn lists_can_be_joined_or_indexed() {
+    let (mut binder, backend) = new_binder(vec![
+        ("仲間", "{party}"),
+        ("先頭", "{party.0}"),
+        ("外", "{party.5}"),
+    ]);
+    binder.set_state_value(
+        "party",
+        StateValue::List(vec![text("勇者"), text("戦士"), number(3.0)]),
+    );
+    binder.update();
+    assert_eq!(values(&backend), vec!["勇者, 戦士, 3", "勇者", ""]);
+}
+
+#[test]
+fn braces_can_be_escaped() {
+    let (mut binder, backend) = new_binder(vec![
+        ("括弧", "{{hp}}"),
+        ("閉じていない", "{hp"),
+        ("空白", "{ hp }"),
+    ]);
+    binder.set_state_value("hp", number(7.0));
+    binder.update();
+    assert_eq!(values(&backend), vec!["{hp}", "{hp", "7"]);
+}
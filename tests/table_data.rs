@@ -0,0 +1,81 @@
+//! Binder::set_table_rows で渡した値を表に表示する確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{strings, texts};
+use std::cell::RefCell;
+use std::rc::Rc;
+use window_lib::binder::Binder;
+use window_lib::figure_builder::FigureBuilder;
+use window_lib::render_backend::MemoryBackend;
+use window_lib::state_store::StateValue;
+
+fn new_binder() -> (Binder, MemoryBackend) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    // 空の表に合わせて縮めない
+    binder.add_figure(
+        &FigureBuilder::new()
+            .content_token("status")
+            .show_content_button(false),
+    );
+    (binder, backend)
+}
+
+#[test]
+fn rows_are_shown_and_replaced() {
+    let (mut binder, backend) = new_binder();
+    binder.set_table_template_rows(
+        "status",
+        strings(&["名前", "HP"]),
+        vec![strings(&["勇者", "{hp}"]), strings(&["戦士", "30"])],
+    );
+    binder.set_state_value("hp", StateValue::Number(12.0));
+    binder.update();
+    assert_eq!(texts(&backend, "status-thead-col-0"), vec!["名前"]);
+    assert_eq!(texts(&backend, "status-tbody-col-0"), vec!["勇者", "戦士"]);
+    assert_eq!(texts(&backend, "status-tbody-col-1"), vec!["12", "30"]);
+    // 登録し直すと内容が入れ替わる
+    binder.set_table_rows("status", vec![], vec![strings(&["魔法使い", "20"])]);
+    binder.update();
+    assert!(backend.find_by_id("status-thead").is_none());
+    assert_eq!(texts(&backend, "status-tbody-col-0"), vec!["魔法使い"]);
+    assert_eq!(texts(&backend, "status-tbody-col-1"), vec!["20"]);
+}
+
+#[test]
+fn rows_can_be_removed() {
+    let (mut binder, backend) = new_binder();
+    binder.set_table_rows("status", vec![], vec![strings(&["勇者", "50"])]);
+    binder.update();
+    assert_eq!(texts(&backend, "status-tbody-col-0"), vec!["勇者"]);
+    assert!(binder.remove_table_content("status"));
+    binder.update();
+    assert!(texts(&backend, "status-tbody-col-0").is_empty());
+}
+
+#[test]
+fn plain_rows_are_shown_as_is() {
+    let (mut binder, backend) = new_binder();
+    let errors = Rc::new(RefCell::new(vec![]));
+    let errors_clone = errors.clone();
+    binder.set_on_template_error_handler(Box::new(move |message| {
+        errors_clone.borrow_mut().push(message.to_string());
+    }));
+    binder.set_table_rows(
+        "status",
+        strings(&["{name}"]),
+        vec![strings(&["{hp}"]), strings(&["a}"]), strings(&["{'b"])],
+    );
+    binder.set_state_value("hp", StateValue::Number(12.0));
+    binder.update();
+    // 式として評価せず、解析もしない
+    assert_eq!(texts(&backend, "status-thead-col-0"), vec!["{name}"]);
+    assert_eq!(
+        texts(&backend, "status-tbody-col-0"),
+        vec!["{hp}", "a}", "{'b"]
+    );
+    assert!(errors.borrow().is_empty());
+}
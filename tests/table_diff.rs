@@ -0,0 +1,141 @@
+//! 表の値が変わったセルだけを書き換える確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use window_lib::binder::{Binder, TableContent};
+use window_lib::figure_builder::FigureBuilder;
+use window_lib::render_backend::{MemoryBackend, NodeId, RenderBackend};
+
+// 値を後から書き換えられる内容
+struct SharedTable {
+    rows: Rc<RefCell<Vec<Vec<String>>>>,
+}
+
+impl TableContent for SharedTable {
+    fn get_tbody(&self, _key: &str) -> Vec<Vec<String>> {
+        self.rows.borrow().clone()
+    }
+}
+
+// 呼ばれた回数を値として返す内容。毎回値が変わるので、毎フレーム描き直す
+struct CountingTable {
+    calls: Rc<Cell<usize>>,
+}
+
+impl TableContent for CountingTable {
+    fn get_tbody(&self, _key: &str) -> Vec<Vec<String>> {
+        self.calls.set(self.calls.get() + 1);
+        vec![row("回数", self.calls.get().to_string().as_str())]
+    }
+}
+
+fn row(name: &str, value: &str) -> Vec<String> {
+    vec![name.to_string(), value.to_string()]
+}
+
+// 100, 100 に 2 列の表を置く
+fn new_binder() -> (Binder, MemoryBackend, Rc<RefCell<Vec<Vec<String>>>>) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    let rows = Rc::new(RefCell::new(vec![
+        row("行動順", "後攻"),
+        row("HP/MHP", "50/50"),
+    ]));
+    binder.set_table_content_state(Box::new(SharedTable { rows: rows.clone() }));
+    binder.add_figure(
+        &FigureBuilder::new()
+            .width(300.0, 180.0, f64::INFINITY)
+            .height(200.0, 30.0, f64::INFINITY)
+            .show_content_button(false),
+    );
+    binder.update();
+    (binder, backend, rows)
+}
+
+// 列ごとの tspan
+fn cells(backend: &MemoryBackend) -> Vec<Vec<NodeId>> {
+    let group = backend
+        .children(backend.container())
+        .into_iter()
+        .find(|node| backend.tag_name(*node) == "g")
+        .unwrap();
+    let content_group = backend.children(group)[3];
+    backend
+        .children(content_group)
+        .into_iter()
+        .filter(|node| backend.tag_name(*node) == "text")
+        .map(|text| backend.children(text))
+        .collect()
+}
+
+fn texts(backend: &MemoryBackend, tspans: &[NodeId]) -> Vec<String> {
+    tspans.iter().map(|tspan| backend.text(*tspan)).collect()
+}
+
+#[test]
+fn changed_cell_is_rewritten_in_place() {
+    let (mut binder, backend, rows) = new_binder();
+    let before = cells(&backend);
+    rows.borrow_mut()[1][1] = "42/50".to_string();
+    binder.update();
+    let after = cells(&backend);
+    // 要素は作り直さない
+    assert_eq!(before, after);
+    assert_eq!(texts(&backend, &after[0]), vec!["行動順", "HP/MHP"]);
+    assert_eq!(texts(&backend, &after[1]), vec!["後攻", "42/50"]);
+}
+
+#[test]
+fn unchanged_values_keep_text() {
+    let (mut binder, backend, _) = new_binder();
+    let before = cells(&backend);
+    binder.update();
+    binder.update();
+    let after = cells(&backend);
+    assert_eq!(before, after);
+    assert_eq!(texts(&backend, &after[1]), vec!["後攻", "50/50"]);
+}
+
+#[test]
+fn added_and_removed_rows_keep_other_cells() {
+    let (mut binder, backend, rows) = new_binder();
+    let before = cells(&backend);
+    rows.borrow_mut().push(row("被ダメ", "5"));
+    binder.update();
+    let added = cells(&backend);
+    assert_eq!(added[0][..2], before[0][..]);
+    assert_eq!(
+        texts(&backend, &added[0]),
+        vec!["行動順", "HP/MHP", "被ダメ"]
+    );
+    assert_eq!(
+        backend.get_attribute(added[0][2], "dy"),
+        Some("50".to_string())
+    );
+    rows.borrow_mut().remove(0);
+    binder.update();
+    // 先頭の行を消すと、後ろの行の値が 1 行ずつ繰り上がる
+    let removed = cells(&backend);
+    assert_eq!(removed[0], before[0]);
+    assert_eq!(texts(&backend, &removed[0]), vec!["HP/MHP", "被ダメ"]);
+    assert_eq!(texts(&backend, &removed[1]), vec!["50/50", "5"]);
+}
+
+#[test]
+fn content_is_fetched_once_per_update() {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    let calls = Rc::new(Cell::new(0));
+    binder.set_table_content_state(Box::new(CountingTable {
+        calls: calls.clone(),
+    }));
+    binder.add_figure(&FigureBuilder::new().show_content_button(false));
+    binder.update();
+    let before = calls.get();
+    binder.update();
+    assert_eq!(calls.get(), before + 1);
+    let tspan = cells(&backend)[1][0];
+    assert_eq!(backend.text(tspan), calls.get().to_string());
+}
@@ -0,0 +1,264 @@
+//! TableStyle で表のセルと行を装飾する確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::content_group;
+use window_lib::binder::Binder;
+use window_lib::figure_builder::FigureBuilder;
+use window_lib::render_backend::{MemoryBackend, NodeId, RenderBackend};
+use window_lib::state_store::StateValue;
+use window_lib::table_style::{CellStyle, StyleRule, TableStyle};
+
+// 名前と HP の 2 列 4 行の表
+fn new_binder() -> (Binder, MemoryBackend) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    let rows = [
+        ("勇者", "{hp}"),
+        ("戦士", "30"),
+        ("魔法使い", "5"),
+        ("僧侶", "8"),
+    ];
+    binder.set_table_template_rows(
+        "status",
+        vec![],
+        rows.iter()
+            .map(|(name, hp)| vec![name.to_string(), hp.to_string()])
+            .collect(),
+    );
+    binder.set_state_value("hp", StateValue::Number(50.0));
+    binder.set_state_value("mhp", StateValue::Number(50.0));
+    binder.add_figure(
+        &FigureBuilder::new()
+            .content_token("status")
+            .show_content_button(false),
+    );
+    binder.update();
+    (binder, backend)
+}
+
+fn tspan(backend: &MemoryBackend, row: usize, column: usize) -> NodeId {
+    let column = backend
+        .find_by_id(format!("status-tbody-col-{}", column).as_str())
+        .unwrap();
+    backend.children(column)[row]
+}
+
+// row 行 column 列の (fill, font-weight, font-style)
+fn cell_style(
+    backend: &MemoryBackend,
+    row: usize,
+    column: usize,
+) -> (Option<String>, Option<String>, Option<String>) {
+    let tspan = tspan(backend, row, column);
+    (
+        backend.get_attribute(tspan, "fill"),
+        backend.get_attribute(tspan, "font-weight"),
+        backend.get_attribute(tspan, "font-style"),
+    )
+}
+
+fn fill(backend: &MemoryBackend, row: usize, column: usize) -> Option<String> {
+    cell_style(backend, row, column).0
+}
+
+// 行の背景の (y, width, fill)
+fn row_backgrounds(backend: &MemoryBackend) -> Vec<(String, String, String)> {
+    let Some(group) = backend
+        .children(content_group(backend))
+        .into_iter()
+        .find(|node| backend.tag_name(*node) == "g")
+    else {
+        return vec![];
+    };
+    backend
+        .children(group)
+        .into_iter()
+        .map(|rect| {
+            (
+                backend.get_attribute(rect, "y").unwrap(),
+                backend.get_attribute(rect, "width").unwrap(),
+                backend.get_attribute(rect, "fill").unwrap(),
+            )
+        })
+        .collect()
+}
+
+fn style(fill: &str) -> CellStyle {
+    CellStyle {
+        fill: Some(fill.to_string()),
+        ..CellStyle::default()
+    }
+}
+
+#[test]
+fn rows_and_cells_can_be_styled() {
+    let (mut binder, backend) = new_binder();
+    binder
+        .set_table_style_value(
+            "status",
+            TableStyle {
+                stripe: None,
+                rules: vec![
+                    StyleRule {
+                        row: Some(1),
+                        style: CellStyle {
+                            fill: Some("blue".to_string()),
+                            font_weight: Some("bold".to_string()),
+                            ..CellStyle::default()
+                        },
+                        ..StyleRule::default()
+                    },
+                    // 後のルールが上書きする
+                    StyleRule {
+                        row: Some(1),
+                        column: Some(1),
+                        style: CellStyle {
+                            fill: Some("green".to_string()),
+                            italic: Some(true),
+                            ..CellStyle::default()
+                        },
+                        ..StyleRule::default()
+                    },
+                ],
+            },
+        )
+        .unwrap();
+    binder.update();
+    assert_eq!(cell_style(&backend, 0, 0), (None, None, None));
+    let some = |value: &str| Some(value.to_string());
+    assert_eq!(
+        cell_style(&backend, 1, 0),
+        (some("blue"), some("bold"), None)
+    );
+    assert_eq!(
+        cell_style(&backend, 1, 1),
+        (some("green"), some("bold"), some("italic"))
+    );
+    // 外すと属性も消える
+    let tspan = tspan(&backend, 1, 1);
+    assert!(binder.remove_table_style("status"));
+    assert!(!binder.remove_table_style("status"));
+    binder.update();
+    assert_eq!(cell_style(&backend, 1, 1), (None, None, None));
+    // tspan は作り直さない
+    assert_eq!(self::tspan(&backend, 1, 1), tspan);
+}
+
+#[test]
+fn conditions_follow_state_and_values() {
+    let (mut binder, backend) = new_binder();
+    binder
+        .set_table_style_value(
+            "status",
+            TableStyle {
+                stripe: None,
+                rules: vec![
+                    StyleRule {
+                        row: Some(0),
+                        column: Some(1),
+                        when: Some("hp < mhp * 0.3".to_string()),
+                        style: style("red"),
+                    },
+                    StyleRule {
+                        row: None,
+                        column: Some(1),
+                        when: Some("value < 10".to_string()),
+                        style: style("orange"),
+                    },
+                ],
+            },
+        )
+        .unwrap();
+    binder.update();
+    let fills = |backend: &MemoryBackend| (0..4).map(|n| fill(backend, n, 1)).collect::<Vec<_>>();
+    let orange = Some("orange".to_string());
+    assert_eq!(
+        fills(&backend),
+        vec![None, None, orange.clone(), orange.clone()]
+    );
+    binder.set_state_value("hp", StateValue::Number(9.0));
+    binder.update();
+    // 両方の条件を満たすと後のルールが勝つ
+    assert_eq!(fills(&backend)[0], orange);
+    binder.set_state_value("hp", StateValue::Number(12.0));
+    binder.update();
+    assert_eq!(fills(&backend)[0], Some("red".to_string()));
+    assert_eq!(fill(&backend, 0, 0), None);
+}
+
+#[test]
+fn row_backgrounds_are_drawn_behind_text() {
+    let (mut binder, backend) = new_binder();
+    binder
+        .set_table_style_value(
+            "status",
+            TableStyle {
+                stripe: Some("#f4f4f4".to_string()),
+                rules: vec![StyleRule {
+                    row: None,
+                    column: None,
+                    when: Some("hp <= 0".to_string()),
+                    style: CellStyle {
+                        background: Some("#fcc".to_string()),
+                        ..CellStyle::default()
+                    },
+                }],
+            },
+        )
+        .unwrap();
+    binder.update();
+    let backgrounds = row_backgrounds(&backend);
+    assert_eq!(backgrounds.len(), 2);
+    assert_eq!(backgrounds[0].0, "25");
+    assert_eq!(backgrounds[1].0, "75");
+    assert!(backgrounds
+        .iter()
+        .all(|(_, width, fill)| width != "0" && fill == "#f4f4f4"));
+    // 背景の g は文字より前に置く
+    let children = backend.children(content_group(&backend));
+    let position = |tag: &str| {
+        children
+            .iter()
+            .position(|node| backend.tag_name(*node) == tag)
+            .unwrap()
+    };
+    assert!(position("g") < position("text"));
+    binder.set_state_value("hp", StateValue::Number(0.0));
+    binder.update();
+    let fills: Vec<String> = row_backgrounds(&backend)
+        .into_iter()
+        .map(|(_, _, fill)| fill)
+        .collect();
+    assert_eq!(fills, vec!["#fcc", "#fcc", "#fcc", "#fcc"]);
+}
+
+#[test]
+fn invalid_conditions_are_rejected() {
+    let (mut binder, backend) = new_binder();
+    let result = binder.set_table_style_value(
+        "status",
+        TableStyle {
+            stripe: None,
+            rules: vec![
+                StyleRule {
+                    style: style("red"),
+                    ..StyleRule::default()
+                },
+                StyleRule {
+                    when: Some("hp < ".to_string()),
+                    style: style("red"),
+                    ..StyleRule::default()
+                },
+            ],
+        },
+    );
+    assert_eq!(
+        result,
+        Err("rules[1].when の 6 文字目: 式がありません".to_string())
+    );
+    binder.update();
+    assert_eq!(fill(&backend, 0, 0), None);
+}
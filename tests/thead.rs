@@ -0,0 +1,144 @@
+//! 表の見出しの表示と、スクロールしても上端に留まることの確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::content_group;
+use std::cell::RefCell;
+use std::rc::Rc;
+use window_lib::binder::{Binder, TableContent};
+use window_lib::figure_builder::{FigureBuilder, TextAnchorType};
+use window_lib::render_backend::{MemoryBackend, NodeId, RenderBackend};
+
+// 見出しを後から書き換えられる 2 列 20 行の表
+struct SharedTable {
+    thead: Rc<RefCell<Vec<String>>>,
+}
+
+impl TableContent for SharedTable {
+    fn get_thead(&self, _key: &str) -> Vec<String> {
+        self.thead.borrow().clone()
+    }
+    fn get_tbody(&self, _key: &str) -> Vec<Vec<String>> {
+        (0..20)
+            .map(|n| vec![format!("name {}", n), format!("{}", n)])
+            .collect()
+    }
+}
+
+// 100, 100 に縦にも横にもはみ出す表を置く。値の列は 205 で右に揃える
+fn new_binder() -> (Binder, MemoryBackend, Rc<RefCell<Vec<String>>>) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    let thead = Rc::new(RefCell::new(vec!["名前".to_string(), "値".to_string()]));
+    binder.set_table_content_state(Box::new(SharedTable {
+        thead: thead.clone(),
+    }));
+    binder.add_figure(
+        &FigureBuilder::new()
+            .width(180.0, 100.0, f64::INFINITY)
+            .height(130.0, 30.0, f64::INFINITY)
+            .show_content_button(false)
+            .fixed_column(TextAnchorType::Start, 100.0)
+            .fixed_column(TextAnchorType::End, 90.0),
+    );
+    binder.update();
+    (binder, backend, thead)
+}
+
+fn find(backend: &MemoryBackend, id: &str) -> NodeId {
+    backend.find_by_id(id).unwrap()
+}
+
+fn translate(backend: &MemoryBackend, node: NodeId) -> (f64, f64) {
+    let transform = backend.get_attribute(node, "transform").unwrap();
+    let values: Vec<f64> = transform
+        .trim_start_matches("translate(")
+        .trim_end_matches(')')
+        .split(", ")
+        .map(|value| (value.parse::<f64>().unwrap() * 1e6).round() / 1e6)
+        .collect();
+    (values[0], values[1])
+}
+
+// 見出しの g の、Figure の中での位置
+fn header_position(backend: &MemoryBackend) -> (f64, f64) {
+    let (content_x, content_y) = translate(backend, content_group(backend));
+    let (header_x, header_y) = translate(backend, find(backend, "figure-0-thead"));
+    (content_x + header_x, content_y + header_y)
+}
+
+#[test]
+fn header_is_drawn_above_body_rows() {
+    let (_, backend, _) = new_binder();
+    let header_tspan = backend.children(find(&backend, "figure-0-thead-col-0"))[0];
+    assert_eq!(backend.text(header_tspan), "名前");
+    assert_eq!(
+        backend.get_attribute(header_tspan, "font-size"),
+        Some("16".to_string())
+    );
+    // 本体の行は見出しの高さだけ下にずらす
+    let body_tspan = backend.children(find(&backend, "figure-0-tbody-col-0"))[0];
+    assert_eq!(backend.text(body_tspan), "name 0");
+    assert_eq!(
+        backend.get_attribute(body_tspan, "y"),
+        Some("50".to_string())
+    );
+    // 見出しは本体より手前に描く
+    let children = backend.children(content_group(&backend));
+    assert_eq!(children.last(), Some(&find(&backend, "figure-0-thead")));
+}
+
+#[test]
+fn header_stays_on_top_while_body_scrolls_vertically() {
+    let (mut binder, backend, _) = new_binder();
+    assert_eq!(header_position(&backend), (5.0, 30.0));
+    assert!(binder.scroll_by(0, 0.0, 100.0));
+    binder.update();
+    assert_eq!(translate(&backend, content_group(&backend)).1, -70.0);
+    assert_eq!(header_position(&backend), (5.0, 30.0));
+    // 背景が表示領域の幅を覆う
+    let background = backend.children(find(&backend, "figure-0-thead"))[0];
+    assert_eq!(
+        backend.get_attribute(background, "width"),
+        Some("170".to_string())
+    );
+}
+
+#[test]
+fn header_scrolls_horizontally_with_body() {
+    let (mut binder, backend, _) = new_binder();
+    assert!(binder.scroll_by(0, 20.0, 0.0));
+    binder.update();
+    let (content_x, _) = translate(&backend, content_group(&backend));
+    assert!(content_x < 5.0);
+    assert_eq!(header_position(&backend), (content_x, 30.0));
+    let background = backend.children(find(&backend, "figure-0-thead"))[0];
+    assert_eq!(
+        backend.get_attribute(background, "x"),
+        Some((5.0 - content_x).to_string())
+    );
+}
+
+#[test]
+fn header_value_change_is_rewritten_in_place() {
+    let (mut binder, backend, thead) = new_binder();
+    let header_tspan = backend.children(find(&backend, "figure-0-thead-col-1"))[0];
+    thead.borrow_mut()[1] = "HP".to_string();
+    binder.update();
+    assert_eq!(
+        backend.children(find(&backend, "figure-0-thead-col-1"))[0],
+        header_tspan
+    );
+    assert_eq!(backend.text(header_tspan), "HP");
+    // 見出しが無くなると本体の行は上に詰める
+    thead.borrow_mut().clear();
+    binder.update();
+    assert!(backend.find_by_id("figure-0-thead").is_none());
+    let body_tspan = backend.children(find(&backend, "figure-0-tbody-col-0"))[0];
+    assert_eq!(
+        backend.get_attribute(body_tspan, "y"),
+        Some("25".to_string())
+    );
+}
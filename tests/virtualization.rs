@@ -0,0 +1,105 @@
+//! 表示領域に入る行だけを描く表の確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::{content_group, figure_group};
+use window_lib::binder::{Binder, TableContent};
+use window_lib::figure_builder::{ContentKind, FigureBuilder};
+use window_lib::render_backend::{MemoryBackend, RenderBackend};
+
+// row_count 行の 1 列の表
+struct ManyRows {
+    row_count: usize,
+}
+
+impl TableContent for ManyRows {
+    fn get_tbody(&self, _key: &str) -> Vec<Vec<String>> {
+        (0..self.row_count)
+            .map(|n| vec![format!("row {}", n)])
+            .collect()
+    }
+}
+
+// 100, 100 に表示領域の高さ 95 の表を置く
+fn new_binder(row_count: usize) -> (Binder, MemoryBackend) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    binder.set_table_content_state(Box::new(ManyRows { row_count }));
+    binder.add_figure(
+        &FigureBuilder::new()
+            .width(300.0, 180.0, f64::INFINITY)
+            .height(130.0, 30.0, f64::INFINITY)
+            .content_kind(ContentKind::Log)
+            .show_content_button(false),
+    );
+    binder.update();
+    (binder, backend)
+}
+
+// 描画されている行の (dy, 値)
+fn rendered_rows(backend: &MemoryBackend) -> Vec<(f64, String)> {
+    let text = backend
+        .children(content_group(backend))
+        .into_iter()
+        .find(|node| backend.tag_name(*node) == "text")
+        .unwrap();
+    backend
+        .children(text)
+        .into_iter()
+        .map(|tspan| {
+            (
+                backend.get_attribute(tspan, "dy").unwrap().parse().unwrap(),
+                backend.text(tspan),
+            )
+        })
+        .collect()
+}
+
+fn scroll_bar_y_height(backend: &MemoryBackend) -> f64 {
+    let scroll_bar_y = backend.children(figure_group(backend))[5];
+    backend
+        .get_attribute(scroll_bar_y, "height")
+        .unwrap()
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn only_rows_in_viewport_are_rendered() {
+    let (_, backend) = new_binder(10000);
+    // 表示領域の 4 行と、その後ろの 1 行
+    let rows = rendered_rows(&backend);
+    assert_eq!(rows.len(), 5);
+    assert_eq!(rows[0], (0.0, "row 0".to_string()));
+    assert_eq!(rows[4], (100.0, "row 4".to_string()));
+}
+
+#[test]
+fn scrolling_renders_rows_at_their_position() {
+    let (mut binder, backend) = new_binder(10000);
+    assert!(binder.scroll_to(0, f64::NAN, 25.0 * 5000.0));
+    binder.update();
+    let rows = rendered_rows(&backend);
+    assert_eq!(rows.len(), 6);
+    assert_eq!(rows[0], (25.0 * 4999.0, "row 4999".to_string()));
+    assert_eq!(rows[5], (25.0 * 5004.0, "row 5004".to_string()));
+    // 末尾まで送ると最後の行が描かれる
+    assert!(binder.scroll_to(0, f64::NAN, f64::INFINITY));
+    binder.update();
+    let rows = rendered_rows(&backend);
+    assert_eq!(rows.last().unwrap().1, "row 9999");
+    assert!(rows.len() <= 6);
+}
+
+#[test]
+fn thumb_length_follows_row_count() {
+    let (_, few) = new_binder(10);
+    let (_, many) = new_binder(100);
+    // 描画している行数は同じでも、つまみの長さは行数の高さ（と余白 10）に反比例する
+    assert_eq!(rendered_rows(&few).len(), rendered_rows(&many).len());
+    let ratio = scroll_bar_y_height(&few) / scroll_bar_y_height(&many);
+    let expected = (25.0 * 100.0 + 10.0) / (25.0 * 10.0 + 10.0);
+    assert!((ratio - expected).abs() < 1e-6, "{}", ratio);
+}
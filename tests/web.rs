@@ -11,3 +11,142 @@ wasm_bindgen_test_configure!(run_in_browser);
 fn pass() {
     assert_eq!(1 + 1, 2);
 }
+
+mod common;
+
+use common::texts;
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::JsValue;
+use window_lib::binder::Binder;
+use window_lib::figure_builder::FigureBuilder;
+use window_lib::render_backend::{MemoryBackend, RenderBackend};
+
+fn new_binder() -> (Binder, MemoryBackend) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    binder.add_figure(
+        &FigureBuilder::new()
+            .content_token("status")
+            .show_content_button(false),
+    );
+    (binder, backend)
+}
+
+fn evaluate(source: &str) -> JsValue {
+    js_sys::Function::new_no_args(source)
+        .call0(&JsValue::NULL)
+        .unwrap()
+}
+
+#[wasm_bindgen_test]
+fn js_object_provides_table() {
+    let (mut binder, backend) = new_binder();
+    let provider = evaluate(
+        "return { getThead: (key) => ['名前', key], getTbody: () => [['勇者', 50], ['戦士', null]] };",
+    );
+    assert!(binder.set_table_provider("status", provider));
+    assert!(!binder.set_table_provider("status", JsValue::from(1)));
+    binder.update();
+    assert_eq!(texts(&backend, "status-thead-col-1"), vec!["status"]);
+    assert_eq!(texts(&backend, "status-tbody-col-0"), vec!["勇者", "戦士"]);
+    assert_eq!(texts(&backend, "status-tbody-col-1"), vec!["50", ""]);
+}
+
+#[wasm_bindgen_test]
+fn js_function_provides_tbody() {
+    let (mut binder, backend) = new_binder();
+    let provider = evaluate("return (key) => [['key', key]];");
+    assert!(binder.set_default_table_provider(provider));
+    binder.update();
+    assert_eq!(texts(&backend, "status-tbody-col-1"), vec!["status"]);
+    // 例外を投げると空の表になる
+    let provider = evaluate("return { getTbody: () => { throw new Error('x'); } };");
+    assert!(binder.set_table_provider("status", provider));
+    binder.update();
+    assert!(texts(&backend, "status-tbody-col-0").is_empty());
+}
+
+#[wasm_bindgen_test]
+fn provider_exceptions_are_reported() {
+    let (mut binder, backend) = new_binder();
+    let errors = Rc::new(RefCell::new(vec![]));
+    let errors_clone = errors.clone();
+    binder.set_on_template_error_handler(Box::new(move |message| {
+        errors_clone.borrow_mut().push(message.to_string());
+    }));
+    let provider = evaluate(
+        "let fails = true; return { getThead: () => { throw 'no thead'; }, \
+         getTbody: () => { if (fails) { fails = false; throw new Error('not ready'); } return [['勇者']]; } };",
+    );
+    assert!(binder.set_table_provider("status", provider));
+    binder.update();
+    assert_eq!(
+        *errors.borrow(),
+        vec![
+            "status の getThead: no thead",
+            "status の getTbody: not ready"
+        ]
+    );
+    // 同じ例外が続く間は通知しない
+    binder.update();
+    binder.update();
+    assert_eq!(errors.borrow().len(), 2);
+    assert_eq!(texts(&backend, "status-tbody-col-0"), vec!["勇者"]);
+}
+
+#[wasm_bindgen_test]
+fn templates_are_evaluated_only_when_enabled() {
+    let (mut binder, backend) = new_binder();
+    binder.set_state("hp", JsValue::from(12));
+    let provider = evaluate("return { getTbody: () => [['{hp}']] };");
+    assert!(binder.set_table_provider("status", provider));
+    binder.update();
+    assert_eq!(texts(&backend, "status-tbody-col-0"), vec!["{hp}"]);
+    let provider = evaluate("return { templates: true, getTbody: () => [['{hp}']] };");
+    assert!(binder.set_table_provider("status", provider));
+    binder.update();
+    assert_eq!(texts(&backend, "status-tbody-col-0"), vec!["12"]);
+    let tbody = evaluate("return [['{hp}']];");
+    assert!(binder.set_table_data("status", JsValue::UNDEFINED, tbody.clone()));
+    binder.update();
+    assert_eq!(texts(&backend, "status-tbody-col-0"), vec!["{hp}"]);
+    assert!(binder.set_table_template_data("status", JsValue::UNDEFINED, tbody));
+    binder.update();
+    assert_eq!(texts(&backend, "status-tbody-col-0"), vec!["12"]);
+}
+
+#[wasm_bindgen_test]
+fn table_data_is_validated() {
+    let (mut binder, backend) = new_binder();
+    let tbody = evaluate("return [['勇者', 50]];");
+    assert!(binder.set_table_data("status", JsValue::UNDEFINED, tbody.clone()));
+    assert!(!binder.set_table_data("status", JsValue::UNDEFINED, evaluate("return [1];")));
+    assert!(!binder.set_table_data("status", JsValue::from_str("名前"), tbody));
+    binder.update();
+    assert_eq!(texts(&backend, "status-tbody-col-0"), vec!["勇者"]);
+}
+
+#[wasm_bindgen_test]
+fn table_style_is_read_from_js() {
+    let (mut binder, backend) = new_binder();
+    binder.set_table_data(
+        "status",
+        JsValue::UNDEFINED,
+        evaluate("return [['勇者', 5]];"),
+    );
+    let style = evaluate(
+        "return { stripe: '#eee', rules: [{ column: 1, when: 'value < 10', fill: 'red', fontWeight: 'bold' }] };",
+    );
+    assert_eq!(binder.set_table_style("status", style), Ok(()));
+    assert_eq!(
+        binder.set_table_style("status", evaluate("return { rules: [{ row: -1 }] };")),
+        Err("rules[0].row は 0 以上の整数で指定します".to_string())
+    );
+    binder.update();
+    let tspan = backend.children(backend.find_by_id("status-tbody-col-1").unwrap())[0];
+    assert_eq!(
+        backend.get_attribute(tspan, "fill"),
+        Some("red".to_string())
+    );
+}
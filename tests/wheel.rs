@@ -0,0 +1,79 @@
+//! ホイールでのスクロールの確認
+
+#![cfg(not(target_arch = "wasm32"))]
+
+mod common;
+
+use common::content_offset;
+use window_lib::binder::Binder;
+use window_lib::figure_builder::FigureBuilder;
+use window_lib::render_backend::MemoryBackend;
+
+// 100, 100 に内容がはみ出す小さな Figure を置く
+fn new_binder() -> (Binder, MemoryBackend) {
+    let backend = MemoryBackend::new();
+    let mut binder = Binder::new_with_backend(Box::new(backend.clone()));
+    binder.set_dummy_state();
+    binder.add_figure(
+        &FigureBuilder::new()
+            .width(120.0, 100.0, f64::INFINITY)
+            .height(80.0, 30.0, f64::INFINITY)
+            .show_content_button(false),
+    );
+    binder.update();
+    (binder, backend)
+}
+
+#[test]
+fn wheel_scrolls_vertically() {
+    let (mut binder, backend) = new_binder();
+    let (x, y) = content_offset(&backend);
+    assert!(binder.wheel(150.0, 160.0, 0.0, 10.0, 0, false));
+    binder.update();
+    assert_eq!(content_offset(&backend), (x, y - 10.0));
+}
+
+#[test]
+fn shift_wheel_scrolls_horizontally() {
+    let (mut binder, backend) = new_binder();
+    let (x, y) = content_offset(&backend);
+    assert!(binder.wheel(150.0, 160.0, 0.0, 10.0, 0, true));
+    binder.update();
+    assert_eq!(content_offset(&backend), (x - 10.0, y));
+}
+
+#[test]
+fn line_mode_scrolls_by_line_height() {
+    let (mut binder, backend) = new_binder();
+    let (x, y) = content_offset(&backend);
+    assert!(binder.wheel(150.0, 160.0, 0.0, 1.0, 1, false));
+    binder.update();
+    assert_eq!(content_offset(&backend), (x, y - 20.0));
+}
+
+#[test]
+fn wheel_stops_at_end() {
+    let (mut binder, backend) = new_binder();
+    let (x, y) = content_offset(&backend);
+    assert!(binder.wheel(150.0, 160.0, 0.0, 1000.0, 0, false));
+    binder.update();
+    let bottom = content_offset(&backend);
+    assert!(bottom.1 > y - 1000.0);
+    assert!(!binder.wheel(150.0, 160.0, 0.0, 10.0, 0, false));
+    // 1ページ分戻すと先頭に戻る
+    assert!(binder.wheel(150.0, 160.0, 0.0, -1.0, 2, false));
+    binder.update();
+    assert_eq!(content_offset(&backend), (x, y));
+}
+
+#[test]
+fn wheel_outside_scrollable_is_ignored() {
+    let (mut binder, backend) = new_binder();
+    let offset = content_offset(&backend);
+    // タイトルバーの上
+    assert!(!binder.wheel(150.0, 110.0, 0.0, 10.0, 0, false));
+    // Figure の外
+    assert!(!binder.wheel(600.0, 600.0, 0.0, 10.0, 0, false));
+    binder.update();
+    assert_eq!(content_offset(&backend), offset);
+}